@@ -0,0 +1,305 @@
+use cushy::value::{Destination, Dynamic};
+use gstreamer as gst;
+use gstreamer::prelude::*;
+
+use crate::video::VideoEvent;
+use crate::Error;
+
+/// Number of frequency bands reported in [`AudioSpectrum::magnitudes`].
+pub const SPECTRUM_BANDS: u32 = 64;
+
+/// Magnitude reported for silent bands, in dB.
+pub const SPECTRUM_THRESHOLD: i32 = -80;
+
+/// Interval between analysis messages.
+const ANALYSIS_INTERVAL: gst::ClockTime = gst::ClockTime::from_mseconds(50);
+
+/// Number of bands of the [`Equalizer`].
+pub const EQUALIZER_BANDS: usize = 10;
+
+/// Center frequency of each [`Equalizer`] band, in Hz.
+pub const EQUALIZER_FREQUENCIES: [f64; EQUALIZER_BANDS] = [
+    29.0, 59.0, 119.0, 237.0, 474.0, 947.0, 1889.0, 3770.0, 7523.0, 15011.0,
+];
+
+/// Lowest gain of an [`Equalizer`] band, in dB.
+pub const EQUALIZER_MIN_GAIN: f64 = -24.0;
+
+/// Highest gain of an [`Equalizer`] band, in dB.
+pub const EQUALIZER_MAX_GAIN: f64 = 12.0;
+
+const EQUALIZER_NAME: &str = "cushy_equalizer";
+const BALANCE_NAME: &str = "cushy_balance";
+const MONO_NAME: &str = "cushy_mono";
+
+/// Loudness of each audio channel, see [`Video::audio_levels`](crate::video::Video::audio_levels).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AudioLevels {
+    /// Root mean square level per channel in dB, `0.0` being full scale.
+    pub rms: Vec<f64>,
+    /// Peak level per channel in dB, `0.0` being full scale.
+    pub peak: Vec<f64>,
+}
+
+/// Frequency spectrum of the audio, see [`Video::audio_spectrum`](crate::video::Video::audio_spectrum).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AudioSpectrum {
+    /// Magnitude in dB of [`SPECTRUM_BANDS`] bands, spaced evenly from 0 Hz to half the sample rate.
+    /// Bands below [`SPECTRUM_THRESHOLD`] are reported at the threshold.
+    pub magnitudes: Vec<f32>,
+}
+
+/// Gains of the 10-band graphic equalizer, see [`Video::set_equalizer`](crate::video::Video::set_equalizer).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Equalizer {
+    /// Gain in dB of each band in [`EQUALIZER_FREQUENCIES`], clamped to
+    /// [`EQUALIZER_MIN_GAIN`]..=[`EQUALIZER_MAX_GAIN`].
+    pub gains: [f64; EQUALIZER_BANDS],
+}
+
+impl Equalizer {
+    /// Leaves the audio untouched.
+    pub const FLAT: Equalizer = Equalizer {
+        gains: [0.0; EQUALIZER_BANDS],
+    };
+
+    /// Checks if no band is boosted or cut.
+    #[must_use]
+    pub fn is_flat(&self) -> bool {
+        self.gains.iter().all(|gain| *gain == 0.0)
+    }
+}
+
+/// Common [`Equalizer`] settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EqualizerPreset {
+    #[default]
+    Flat,
+    BassBoost,
+    TrebleBoost,
+    Rock,
+    Pop,
+    Classical,
+    /// Brings voices forward, e.g. for dialogue drowned out by music and effects.
+    Speech,
+}
+
+impl From<EqualizerPreset> for Equalizer {
+    fn from(preset: EqualizerPreset) -> Self {
+        let gains = match preset {
+            EqualizerPreset::Flat => [0.0; EQUALIZER_BANDS],
+            EqualizerPreset::BassBoost => [6.0, 5.0, 4.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            EqualizerPreset::TrebleBoost => [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 4.0, 5.0, 6.0],
+            EqualizerPreset::Rock => [5.0, 4.0, 2.0, -1.0, -2.0, -1.0, 1.0, 3.0, 4.0, 5.0],
+            EqualizerPreset::Pop => [-1.0, 1.0, 3.0, 4.0, 3.0, 1.0, -1.0, -1.0, -1.0, -1.0],
+            EqualizerPreset::Classical => [4.0, 3.0, 2.0, 1.0, 0.0, 0.0, 0.0, 1.0, 2.0, 3.0],
+            EqualizerPreset::Speech => [-6.0, -4.0, -2.0, 0.0, 2.0, 4.0, 4.0, 2.0, 0.0, -2.0],
+        };
+        Equalizer { gains }
+    }
+}
+
+/// Options of the `audio-filter` chain installed into `playbin`.
+///
+/// The processing elements are only added once their feature is used, and are then kept
+/// so they can be adjusted with [`configure`] without rebuilding the chain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct AudioFilter {
+    pub(crate) preserve_pitch: bool,
+    pub(crate) equalizer: bool,
+    pub(crate) mono: bool,
+    pub(crate) balance: bool,
+    pub(crate) analysis: bool,
+}
+
+impl AudioFilter {
+    /// Builds the filter bin, `None` if nothing needs filtering.
+    pub(crate) fn build(self) -> Option<gst::Element> {
+        let mut elements = Vec::new();
+        if self.preserve_pitch {
+            // passes audio through untouched at normal speed
+            if gst::ElementFactory::find("scaletempo").is_some() {
+                elements.push("scaletempo".to_string());
+            } else {
+                log::warn!("scaletempo unavailable, speed changes will shift the pitch");
+            }
+        }
+        // the processing elements only take raw float or some integer formats
+        if self.equalizer {
+            elements.push(format!(
+                "audioconvert ! equalizer-10bands name={EQUALIZER_NAME}"
+            ));
+        }
+        if self.mono {
+            elements.push(format!(
+                "audioconvert ! capsfilter name={MONO_NAME} caps=audio/x-raw ! audioconvert"
+            ));
+        }
+        if self.balance {
+            elements.push(format!(
+                "audioconvert ! audiopanorama name={BALANCE_NAME} method=simple"
+            ));
+        }
+        if self.analysis {
+            let interval = ANALYSIS_INTERVAL.nseconds();
+            elements.push(format!(
+                "level name=cushy_level post-messages=true interval={interval}"
+            ));
+            elements.push(format!(
+                "spectrum name=cushy_spectrum post-messages=true interval={interval} \
+                 bands={SPECTRUM_BANDS} threshold={SPECTRUM_THRESHOLD}"
+            ));
+        }
+        if elements.is_empty() {
+            return None;
+        }
+
+        gst::parse::bin_from_description(&elements.join(" ! "), true)
+            .inspect_err(|err| log::warn!("failed to create audio filter: {err}"))
+            .ok()
+            .map(|bin| bin.upcast())
+    }
+}
+
+/// Applies the processing options to a filter bin built by [`AudioFilter::build`],
+/// skipping the elements it doesn't contain.
+pub(crate) fn configure(filter: &gst::Element, equalizer: &Equalizer, balance: f32, mono: bool) {
+    let Some(bin) = filter.downcast_ref::<gst::Bin>() else {
+        return;
+    };
+    if let Some(element) = bin.by_name(EQUALIZER_NAME) {
+        for (band, gain) in equalizer.gains.iter().enumerate() {
+            element.set_property(
+                &format!("band{band}"),
+                gain.clamp(EQUALIZER_MIN_GAIN, EQUALIZER_MAX_GAIN),
+            );
+        }
+    }
+    if let Some(element) = bin.by_name(MONO_NAME) {
+        let caps = if mono {
+            gst::Caps::builder("audio/x-raw")
+                .field("channels", 1)
+                .build()
+        } else {
+            gst::Caps::new_empty_simple("audio/x-raw")
+        };
+        element.set_property("caps", caps);
+    }
+    if let Some(element) = bin.by_name(BALANCE_NAME) {
+        element.set_property("panorama", balance.clamp(-1.0, 1.0));
+    }
+}
+
+/// Reads the levels from a message posted by the `level` element.
+fn levels(structure: &gst::StructureRef) -> Option<AudioLevels> {
+    let channels = |field| {
+        structure
+            .get::<glib::ValueArray>(field)
+            .ok()
+            .map(|values| values.iter().filter_map(|v| v.get::<f64>().ok()).collect())
+    };
+    Some(AudioLevels {
+        rms: channels("rms")?,
+        peak: channels("peak")?,
+    })
+}
+
+/// Reads the magnitudes from a message posted by the `spectrum` element.
+fn spectrum(structure: &gst::StructureRef) -> Option<AudioSpectrum> {
+    let magnitudes = structure.get::<gst::List>("magnitude").ok()?;
+    Some(AudioSpectrum {
+        magnitudes: magnitudes
+            .iter()
+            .filter_map(|v| v.get::<f32>().ok())
+            .collect(),
+    })
+}
+
+/// A result of the `level` or `spectrum` element.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Analysis {
+    Levels(AudioLevels),
+    Spectrum(AudioSpectrum),
+}
+
+/// Reads a message posted by the `level` or `spectrum` element, along with the running time
+/// at which the analyzed audio ends.
+pub(crate) fn analysis(structure: &gst::StructureRef) -> Option<(gst::ClockTime, Analysis)> {
+    let analysis = match structure.name().as_str() {
+        "level" => Analysis::Levels(levels(structure)?),
+        "spectrum" => Analysis::Spectrum(spectrum(structure)?),
+        _ => return None,
+    };
+    let running_time = structure.get::<u64>("running-time").ok()?;
+    let duration = structure.get::<u64>("duration").unwrap_or(0);
+    Some((
+        gst::ClockTime::from_nseconds(running_time + duration),
+        analysis,
+    ))
+}
+
+/// An audio output device, see [`Video::audio_devices`](crate::video::Video::audio_devices).
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioDevice {
+    /// Human readable name, e.g. `Built-in Audio Analog Stereo`.
+    pub name: String,
+    device: gst::Device,
+}
+
+impl AudioDevice {
+    fn new(device: gst::Device) -> Self {
+        AudioDevice {
+            name: device.display_name().to_string(),
+            device,
+        }
+    }
+
+    /// Creates a sink playing to this device.
+    pub(crate) fn create_sink(&self) -> Result<gst::Element, Error> {
+        Ok(self.device.create_element(None)?)
+    }
+}
+
+/// Starts watching the audio output devices, keeping `devices` up to date and
+/// publishing the changes to `events`.
+pub(crate) fn monitor_devices(
+    devices: Dynamic<Vec<AudioDevice>>,
+    events: Dynamic<Option<VideoEvent>>,
+) -> Result<gst::DeviceMonitor, Error> {
+    let monitor = gst::DeviceMonitor::new();
+    monitor.add_filter(Some("Audio/Sink"), None);
+
+    let devices_ref = devices.clone();
+    // called from the thread of the device provider, the bus isn't polled anywhere
+    monitor.bus().set_sync_handler(move |_, msg| {
+        let event = match msg.view() {
+            gst::MessageView::DeviceAdded(added) => {
+                let device = AudioDevice::new(added.device());
+                devices_ref.map_mut(|mut devices| {
+                    if !devices.contains(&device) {
+                        devices.push(device.clone());
+                    }
+                });
+                VideoEvent::AudioDeviceAdded(device)
+            }
+            gst::MessageView::DeviceRemoved(removed) => {
+                let device = AudioDevice::new(removed.device());
+                devices_ref.map_mut(|mut devices| devices.retain(|d| *d != device));
+                VideoEvent::AudioDeviceRemoved(device)
+            }
+            _ => return gst::BusSyncReply::Drop,
+        };
+        events.map_mut(|mut e| *e = Some(event));
+        gst::BusSyncReply::Drop
+    });
+
+    monitor.start()?;
+    devices.set(
+        monitor
+            .devices()
+            .into_iter()
+            .map(AudioDevice::new)
+            .collect(),
+    );
+    Ok(monitor)
+}
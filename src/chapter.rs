@@ -0,0 +1,143 @@
+use std::time::Duration;
+
+use gstreamer as gst;
+
+/// A chapter of the media, read from its table of contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chapter {
+    pub title: Option<String>,
+    pub start: Duration,
+    /// End of the chapter, `None` if it lasts until the end of the media.
+    pub end: Option<Duration>,
+}
+
+/// Collects the chapters of the first edition in `toc`, ordered by their start.
+pub(crate) fn from_toc(toc: &gst::TocRef) -> Vec<Chapter> {
+    fn collect(entries: Vec<gst::TocEntry>, chapters: &mut Vec<Chapter>) {
+        for entry in entries {
+            if entry.entry_type() == gst::TocEntryType::Chapter {
+                if let Some((start, stop)) = entry.start_stop_times() {
+                    chapters.push(Chapter {
+                        title: entry.tags().and_then(|tags| {
+                            tags.get::<gst::tags::Title>()
+                                .map(|title| title.get().to_string())
+                        }),
+                        start: Duration::from_nanos(start.max(0) as u64),
+                        end: u64::try_from(stop).ok().map(Duration::from_nanos),
+                    });
+                }
+            }
+            collect(entry.sub_entries(), chapters);
+        }
+    }
+
+    // editions, e.g. in Matroska, are alternative chapter lists of the same media
+    let entries = toc.entries();
+    let edition = entries
+        .iter()
+        .position(|entry| entry.entry_type() == gst::TocEntryType::Edition);
+    let entries = match edition {
+        Some(index) => entries[index].sub_entries(),
+        None => entries,
+    };
+    let mut chapters = Vec::new();
+    collect(entries, &mut chapters);
+    chapters.sort_by_key(|chapter| chapter.start);
+    chapters
+}
+
+/// Get the index of the chapter playing at `position`.
+pub(crate) fn at(chapters: &[Chapter], position: Duration) -> Option<usize> {
+    chapters.iter().rposition(|chapter| {
+        chapter.start <= position && chapter.end.map_or(true, |end| position < end)
+    })
+}
+
+/// Get the index of the chapter playing at `position`, or of the last one before it
+/// if `position` is in a gap between chapters.
+pub(crate) fn at_or_before(chapters: &[Chapter], position: Duration) -> Option<usize> {
+    at(chapters, position).or_else(|| {
+        chapters
+            .iter()
+            .rposition(|chapter| chapter.start <= position)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapter(start: u64, end: Option<u64>) -> Chapter {
+        Chapter {
+            title: None,
+            start: Duration::from_secs(start),
+            end: end.map(Duration::from_secs),
+        }
+    }
+
+    #[test]
+    fn chapter_at_position() {
+        let chapters = [
+            chapter(0, Some(10)),
+            // a gap between 10 and 20
+            chapter(20, Some(40)),
+            // nested in the previous chapter
+            chapter(25, Some(30)),
+            chapter(40, None),
+        ];
+        let cases = [
+            (0, Some(0)),
+            (9, Some(0)),
+            (10, None),
+            (15, None),
+            (20, Some(1)),
+            (25, Some(2)),
+            (29, Some(2)),
+            (30, Some(1)),
+            (40, Some(3)),
+            (1000, Some(3)),
+        ];
+        for (position, expected) in cases {
+            assert_eq!(
+                at(&chapters, Duration::from_secs(position)),
+                expected,
+                "at {position}s"
+            );
+        }
+        assert_eq!(at(&[], Duration::ZERO), None);
+
+        let cases = [(0, Some(0)), (15, Some(0)), (25, Some(2)), (1000, Some(3))];
+        for (position, expected) in cases {
+            assert_eq!(
+                at_or_before(&chapters, Duration::from_secs(position)),
+                expected,
+                "at or before {position}s"
+            );
+        }
+        let late = [chapter(10, Some(20))];
+        assert_eq!(at_or_before(&late, Duration::from_secs(5)), None);
+    }
+
+    #[test]
+    fn first_edition() {
+        gst::init().unwrap();
+        let mut toc = gst::Toc::new(gst::TocScope::Global);
+        for (edition, starts) in [("first", [0, 30]), ("second", [0, 10])] {
+            let mut entry = gst::TocEntry::new(gst::TocEntryType::Edition, edition);
+            for (index, start) in starts.into_iter().enumerate() {
+                let mut chapter =
+                    gst::TocEntry::new(gst::TocEntryType::Chapter, &format!("{edition}{index}"));
+                let start = gst::ClockTime::from_seconds(start).nseconds() as i64;
+                chapter.get_mut().unwrap().set_start_stop_times(start, -1);
+                entry.get_mut().unwrap().append_sub_entry(chapter);
+            }
+            toc.get_mut().unwrap().append_entry(entry);
+        }
+
+        let starts: Vec<_> = from_toc(&toc)
+            .into_iter()
+            .map(|chapter| chapter.start)
+            .collect();
+        assert_eq!(starts, [Duration::ZERO, Duration::from_secs(30)]);
+    }
+}
@@ -0,0 +1,423 @@
+use gstreamer as gst;
+use gstreamer_video as gst_video;
+
+/// Matrix converting YUV to RGB, named after the standard defining it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMatrix {
+    Bt601,
+    Bt709,
+    Bt2020,
+}
+
+impl ColorMatrix {
+    /// Luma coefficients of red and blue.
+    fn kr_kb(self) -> (f32, f32) {
+        match self {
+            ColorMatrix::Bt601 => (0.299, 0.114),
+            ColorMatrix::Bt709 => (0.2126, 0.0722),
+            ColorMatrix::Bt2020 => (0.2627, 0.0593),
+        }
+    }
+}
+
+/// Value range of the YUV samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorRange {
+    /// Y in 16..=235 and UV in 16..=240, as used by most video.
+    Limited,
+    /// The whole 0..=255 range, as used by JPEG and many cameras.
+    Full,
+}
+
+/// Transfer function the samples are encoded with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferFunction {
+    Bt709,
+    Srgb,
+    /// SMPTE ST 2084, used by HDR10.
+    Pq,
+    /// ARIB STD-B67, used by HDR broadcasts.
+    Hlg,
+}
+
+/// Color primaries the RGB values refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorPrimaries {
+    Bt601,
+    Bt709,
+    Bt2020,
+}
+
+/// How the colors of a video are encoded, read from its caps.
+///
+/// Can be overridden for streams with missing or wrong information,
+/// see [`Video::set_colorimetry_override`](crate::video::Video::set_colorimetry_override).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Colorimetry {
+    pub matrix: ColorMatrix,
+    pub range: ColorRange,
+    pub transfer: TransferFunction,
+    pub primaries: ColorPrimaries,
+}
+
+impl Default for Colorimetry {
+    fn default() -> Self {
+        Colorimetry {
+            matrix: ColorMatrix::Bt709,
+            range: ColorRange::Limited,
+            transfer: TransferFunction::Bt709,
+            primaries: ColorPrimaries::Bt709,
+        }
+    }
+}
+
+impl Colorimetry {
+    /// Reads the colorimetry of `info`, guessing unknown values from the resolution
+    /// the same way GStreamer and most players do.
+    pub(crate) fn from_info(info: &gst_video::VideoInfo) -> Self {
+        let colorimetry = info.colorimetry();
+        let hd = info.height() >= 720;
+        let uhd = info.height() > 1080;
+
+        let matrix = match colorimetry.matrix() {
+            gst_video::VideoColorMatrix::Bt601 => ColorMatrix::Bt601,
+            gst_video::VideoColorMatrix::Bt709 => ColorMatrix::Bt709,
+            gst_video::VideoColorMatrix::Bt2020 => ColorMatrix::Bt2020,
+            _ if hd => ColorMatrix::Bt709,
+            _ => ColorMatrix::Bt601,
+        };
+        let range = match colorimetry.range() {
+            gst_video::VideoColorRange::Range0_255 => ColorRange::Full,
+            _ => ColorRange::Limited,
+        };
+        let transfer = match colorimetry.transfer() {
+            gst_video::VideoTransferFunction::Srgb => TransferFunction::Srgb,
+            gst_video::VideoTransferFunction::Smpte2084 => TransferFunction::Pq,
+            gst_video::VideoTransferFunction::AribStdB67 => TransferFunction::Hlg,
+            _ => TransferFunction::Bt709,
+        };
+        let primaries = match colorimetry.primaries() {
+            gst_video::VideoColorPrimaries::Bt709 => ColorPrimaries::Bt709,
+            gst_video::VideoColorPrimaries::Bt2020 => ColorPrimaries::Bt2020,
+            gst_video::VideoColorPrimaries::Smpte170m | gst_video::VideoColorPrimaries::Bt470bg => {
+                ColorPrimaries::Bt601
+            }
+            _ if uhd => ColorPrimaries::Bt2020,
+            _ if hd => ColorPrimaries::Bt709,
+            _ => ColorPrimaries::Bt601,
+        };
+
+        Colorimetry {
+            matrix,
+            range,
+            transfer,
+            primaries,
+        }
+    }
+
+    /// Columns of the matrix converting YUV (with [`Colorimetry::yuv_offset`] subtracted) to RGB,
+    /// padded to the WGSL `mat3x3` layout.
+    pub(crate) fn yuv_matrix(&self) -> [[f32; 4]; 3] {
+        let (kr, kb) = self.matrix.kr_kb();
+        let kg = 1.0 - kr - kb;
+        let (y_scale, uv_scale) = match self.range {
+            ColorRange::Limited => (255.0 / 219.0, 255.0 / 224.0),
+            ColorRange::Full => (1.0, 1.0),
+        };
+
+        let y = [y_scale, y_scale, y_scale, 0.0];
+        let u = [
+            0.0,
+            -2.0 * kb * (1.0 - kb) / kg * uv_scale,
+            2.0 * (1.0 - kb) * uv_scale,
+            0.0,
+        ];
+        let v = [
+            2.0 * (1.0 - kr) * uv_scale,
+            -2.0 * kr * (1.0 - kr) / kg * uv_scale,
+            0.0,
+            0.0,
+        ];
+        [y, u, v]
+    }
+
+    /// Value subtracted from the YUV samples before applying [`Colorimetry::yuv_matrix`].
+    pub(crate) fn yuv_offset(&self) -> [f32; 4] {
+        let y = match self.range {
+            ColorRange::Limited => 16.0 / 255.0,
+            ColorRange::Full => 0.0,
+        };
+        [y, 0.5, 0.5, 0.0]
+    }
+
+    /// Columns of the matrix converting linear RGB in these primaries to BT.709, which
+    /// is what the output is displayed in.
+    pub(crate) fn gamut_matrix(&self) -> [[f32; 4]; 3] {
+        match self.primaries {
+            ColorPrimaries::Bt2020 => [
+                [1.6605, -0.1246, -0.0182, 0.0],
+                [-0.5876, 1.1329, -0.1006, 0.0],
+                [-0.0728, -0.0083, 1.1187, 0.0],
+            ],
+            // BT.601 primaries are close enough to not need a conversion
+            ColorPrimaries::Bt601 | ColorPrimaries::Bt709 => [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+            ],
+        }
+    }
+}
+
+/// How HDR (PQ or HLG) video is mapped to the SDR range of the display.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ToneMapping {
+    /// Clips everything brighter than SDR white.
+    Clip,
+    /// Extended Reinhard, simple and keeps dark scenes untouched.
+    Reinhard,
+    /// The filmic curve from Uncharted 2, with a slight contrast boost.
+    Hable,
+    /// The EETF of ITU-R BT.2390, mapping in the perceptual PQ domain.
+    #[default]
+    Bt2390,
+}
+
+/// Brightness, contrast, saturation, hue and gamma adjustments, applied by the shader
+/// to the non-linear RGB values like the picture controls of a TV.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PictureAdjustments {
+    /// Added to every channel, `0.0` leaves the picture untouched.
+    pub(crate) brightness: f32,
+    /// Scales the distance from mid-grey, `1.0` leaves the picture untouched.
+    pub(crate) contrast: f32,
+    /// Scales the distance from the luma, `1.0` leaves the picture untouched.
+    pub(crate) saturation: f32,
+    /// Rotation of the hue in degrees, `0.0` leaves the picture untouched.
+    pub(crate) hue: f32,
+    /// Exponent applied last, `1.0` leaves the picture untouched.
+    pub(crate) gamma: f32,
+}
+
+impl Default for PictureAdjustments {
+    fn default() -> Self {
+        PictureAdjustments {
+            brightness: 0.0,
+            contrast: 1.0,
+            saturation: 1.0,
+            hue: 0.0,
+            gamma: 1.0,
+        }
+    }
+}
+
+/// Luminance of SDR white in cd/m², as recommended by ITU-R BT.2408.
+pub(crate) const SDR_WHITE: f32 = 203.0;
+
+/// Peak luminance assumed for HDR video without mastering metadata, in cd/m².
+pub(crate) const DEFAULT_HDR_PEAK: f32 = 1000.0;
+
+/// Reads the peak luminance of HDR video from its caps, in cd/m².
+/// Prefers the content light level over the mastering display, as it describes the actual content.
+pub(crate) fn peak_luminance(caps: &gst::CapsRef) -> Option<f32> {
+    let content = gst_video::VideoContentLightLevel::from_caps(caps)
+        .ok()
+        .map(|level| level.max_content_light_level())
+        .filter(|level| *level > 0)
+        .map(f32::from);
+    let mastering = || {
+        gst_video::VideoMasteringDisplayInfo::from_caps(caps)
+            .ok()
+            // in units of 0.0001 cd/m²
+            .map(|info| info.max_display_mastering_luminance() as f32 / 10_000.0)
+            .filter(|luminance| *luminance > 0.0)
+    };
+    content.or_else(mastering)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gstreamer_video::{
+        VideoColorMatrix as Matrix, VideoColorPrimaries as Primaries, VideoColorRange as Range,
+        VideoColorimetry, VideoFormat, VideoTransferFunction as Transfer,
+    };
+
+    fn info(height: u32, colorimetry: VideoColorimetry) -> gst_video::VideoInfo {
+        gst_video::VideoInfo::builder(VideoFormat::Nv12, height * 16 / 9, height)
+            .colorimetry(&colorimetry)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn colorimetry_from_info() {
+        gst::init().unwrap();
+        let unknown = || {
+            VideoColorimetry::new(
+                Range::Unknown,
+                Matrix::Unknown,
+                Transfer::Unknown,
+                Primaries::Unknown,
+            )
+        };
+        let cases = [
+            (
+                1080,
+                "bt709".parse().unwrap(),
+                (
+                    ColorMatrix::Bt709,
+                    ColorRange::Limited,
+                    TransferFunction::Bt709,
+                    ColorPrimaries::Bt709,
+                ),
+            ),
+            (
+                480,
+                "bt601".parse().unwrap(),
+                (
+                    ColorMatrix::Bt601,
+                    ColorRange::Limited,
+                    TransferFunction::Bt709,
+                    ColorPrimaries::Bt601,
+                ),
+            ),
+            (
+                2160,
+                "bt2100-pq".parse().unwrap(),
+                (
+                    ColorMatrix::Bt2020,
+                    ColorRange::Limited,
+                    TransferFunction::Pq,
+                    ColorPrimaries::Bt2020,
+                ),
+            ),
+            (
+                1080,
+                "bt2100-hlg".parse().unwrap(),
+                (
+                    ColorMatrix::Bt2020,
+                    ColorRange::Limited,
+                    TransferFunction::Hlg,
+                    ColorPrimaries::Bt2020,
+                ),
+            ),
+            (
+                720,
+                VideoColorimetry::new(
+                    Range::Range0_255,
+                    Matrix::Bt709,
+                    Transfer::Srgb,
+                    Primaries::Bt709,
+                ),
+                (
+                    ColorMatrix::Bt709,
+                    ColorRange::Full,
+                    TransferFunction::Srgb,
+                    ColorPrimaries::Bt709,
+                ),
+            ),
+            // guessed from the resolution
+            (
+                480,
+                unknown(),
+                (
+                    ColorMatrix::Bt601,
+                    ColorRange::Limited,
+                    TransferFunction::Bt709,
+                    ColorPrimaries::Bt601,
+                ),
+            ),
+            (
+                1080,
+                unknown(),
+                (
+                    ColorMatrix::Bt709,
+                    ColorRange::Limited,
+                    TransferFunction::Bt709,
+                    ColorPrimaries::Bt709,
+                ),
+            ),
+            (
+                2160,
+                unknown(),
+                (
+                    ColorMatrix::Bt709,
+                    ColorRange::Limited,
+                    TransferFunction::Bt709,
+                    ColorPrimaries::Bt2020,
+                ),
+            ),
+        ];
+
+        for (height, colorimetry, (matrix, range, transfer, primaries)) in cases {
+            let name = colorimetry.to_string();
+            assert_eq!(
+                Colorimetry::from_info(&info(height, colorimetry)),
+                Colorimetry {
+                    matrix,
+                    range,
+                    transfer,
+                    primaries,
+                },
+                "{name} at {height}p"
+            );
+        }
+    }
+
+    #[test]
+    fn yuv_matrices() {
+        let colorimetry = |matrix, range| Colorimetry {
+            matrix,
+            range,
+            ..Colorimetry::default()
+        };
+        // columns of the well known conversion matrices
+        let cases = [
+            (
+                colorimetry(ColorMatrix::Bt709, ColorRange::Full),
+                [
+                    [1.0, 1.0, 1.0],
+                    [0.0, -0.1873, 1.8556],
+                    [1.5748, -0.4681, 0.0],
+                ],
+            ),
+            (
+                colorimetry(ColorMatrix::Bt601, ColorRange::Full),
+                [
+                    [1.0, 1.0, 1.0],
+                    [0.0, -0.3441, 1.772],
+                    [1.402, -0.7141, 0.0],
+                ],
+            ),
+            (
+                colorimetry(ColorMatrix::Bt601, ColorRange::Limited),
+                [
+                    [1.1644, 1.1644, 1.1644],
+                    [0.0, -0.3918, 2.0172],
+                    [1.596, -0.813, 0.0],
+                ],
+            ),
+            (
+                colorimetry(ColorMatrix::Bt2020, ColorRange::Full),
+                [
+                    [1.0, 1.0, 1.0],
+                    [0.0, -0.1646, 1.8814],
+                    [1.4746, -0.5714, 0.0],
+                ],
+            ),
+        ];
+
+        for (colorimetry, expected) in cases {
+            let matrix = colorimetry.yuv_matrix();
+            for (column, expected) in matrix.iter().zip(expected) {
+                for (value, expected) in column.iter().zip(expected) {
+                    assert!(
+                        (value - expected).abs() < 1e-3,
+                        "{colorimetry:?}: {matrix:?} != {expected:?}"
+                    );
+                }
+            }
+        }
+    }
+}
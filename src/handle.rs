@@ -0,0 +1,202 @@
+use std::sync::atomic::Ordering;
+use std::sync::{RwLock, Weak};
+use std::time::Duration;
+
+use cushy::value::{Destination, Source};
+use gstreamer::prelude::*;
+
+use crate::audio::{AudioDevice, Equalizer};
+use crate::video::{Internal, Position, TrickMode};
+use crate::Error;
+
+/// A cloneable handle to control a [`Video`](crate::video::Video) from anywhere.
+///
+/// The handle doesn't keep the video alive; once the video is dropped, every method
+/// returns [`Error::VideoDropped`].
+#[derive(Debug, Clone)]
+pub struct VideoHandle(pub(crate) Weak<RwLock<Internal>>);
+
+impl VideoHandle {
+    /// Runs `f` with the write lock held. Dynamics set by `f` would notify their callbacks
+    /// while the lock is held, so `paused`, `is_eos` and the like are set after it returns.
+    fn with<R>(&self, f: impl FnOnce(&mut Internal) -> R) -> Result<R, Error> {
+        let internal = self.0.upgrade().ok_or(Error::VideoDropped)?;
+        let mut inner = internal.write().map_err(|_| Error::Lock)?;
+        if !inner.alive.load(Ordering::SeqCst) {
+            return Err(Error::VideoDropped);
+        }
+        Ok(f(&mut inner))
+    }
+
+    /// Like [`VideoHandle::with`], but only takes the read lock, so getters can be called
+    /// from callbacks running while the video is borrowed elsewhere.
+    fn read<R>(&self, f: impl FnOnce(&Internal) -> R) -> Result<R, Error> {
+        let internal = self.0.upgrade().ok_or(Error::VideoDropped)?;
+        let inner = internal.read().map_err(|_| Error::Lock)?;
+        if !inner.alive.load(Ordering::SeqCst) {
+            return Err(Error::VideoDropped);
+        }
+        Ok(f(&inner))
+    }
+
+    /// Returns `true` while the video is still alive.
+    pub fn is_alive(&self) -> bool {
+        self.read(|_| ()).is_ok()
+    }
+
+    /// Set the volume multiplier of the audio, see [`Video::set_volume`](crate::video::Video::set_volume).
+    pub fn set_volume(&self, volume: f64) -> Result<(), Error> {
+        self.read(|inner| inner.volume.clone())?.set(volume);
+        Ok(())
+    }
+
+    /// Get the volume multiplier of the audio.
+    pub fn volume(&self) -> Result<f64, Error> {
+        self.read(|inner| inner.source.property("volume"))
+    }
+
+    /// Set if the audio is muted or not, without changing the volume.
+    pub fn set_muted(&self, muted: bool) -> Result<(), Error> {
+        self.read(|inner| inner.muted.clone())?.set(muted);
+        Ok(())
+    }
+
+    /// Get if the audio is muted or not.
+    pub fn muted(&self) -> Result<bool, Error> {
+        self.read(|inner| inner.source.property("mute"))
+    }
+
+    /// Set if the media is paused or not.
+    pub fn set_paused(&self, paused: bool) -> Result<(), Error> {
+        // always notifies, see `Video::set_paused`
+        self.read(|inner| inner.paused.clone())?
+            .map_mut(|mut p| *p = paused);
+        Ok(())
+    }
+
+    /// Get if the media is paused or not.
+    pub fn paused(&self) -> Result<bool, Error> {
+        self.read(|inner| inner.paused())
+    }
+
+    /// Pauses the media if it is playing, and resumes it otherwise.
+    pub fn toggle_paused(&self) -> Result<(), Error> {
+        let paused = self.paused()?;
+        self.set_paused(!paused)
+    }
+
+    /// Set if the media will loop or not.
+    pub fn set_looping(&self, looping: bool) -> Result<(), Error> {
+        let ended = self.with(|inner| inner.set_looping(looping))?;
+        if ended {
+            self.read(|inner| inner.is_eos.clone())?.set(false);
+        }
+        Ok(())
+    }
+
+    /// Get if the media will loop or not.
+    pub fn looping(&self) -> Result<bool, Error> {
+        self.read(|inner| inner.looping.load(Ordering::SeqCst))
+    }
+
+    /// Get if the stream ended or not.
+    pub fn eos(&self) -> Result<bool, Error> {
+        self.read(|inner| inner.is_eos.get())
+    }
+
+    /// Jumps to a specific position in the media, see [`Video::seek`](crate::video::Video::seek).
+    pub fn seek(&self, position: impl Into<Position>, accurate: bool) -> Result<(), Error> {
+        self.with(|inner| inner.seek(position, accurate))?
+    }
+
+    /// Set the playback speed of the media.
+    pub fn set_speed(&self, speed: f64) -> Result<(), Error> {
+        self.with(|inner| inner.set_speed(speed))?
+    }
+
+    /// Set if the pitch is kept when changing the speed, see
+    /// [`Video::set_preserve_pitch`](crate::video::Video::set_preserve_pitch).
+    pub fn set_preserve_pitch(&self, preserve_pitch: bool) -> Result<(), Error> {
+        self.with(|inner| inner.set_preserve_pitch(preserve_pitch))?
+    }
+
+    /// Turns the audio analysis on or off, see
+    /// [`Video::set_audio_analysis`](crate::video::Video::set_audio_analysis).
+    pub fn set_audio_analysis(&self, analysis: bool) -> Result<(), Error> {
+        self.with(|inner| inner.set_audio_analysis(analysis))?
+    }
+
+    /// Set the gains of the graphic equalizer, see
+    /// [`Video::set_equalizer`](crate::video::Video::set_equalizer).
+    pub fn set_equalizer(&self, equalizer: impl Into<Equalizer>) -> Result<(), Error> {
+        let equalizer = equalizer.into();
+        self.with(|inner| inner.set_equalizer(equalizer))?
+    }
+
+    /// Set the stereo balance, see [`Video::set_balance`](crate::video::Video::set_balance).
+    pub fn set_balance(&self, balance: f32) -> Result<(), Error> {
+        self.with(|inner| inner.set_balance(balance))?
+    }
+
+    /// Mixes the audio down to mono, see [`Video::set_mono`](crate::video::Video::set_mono).
+    pub fn set_mono(&self, mono: bool) -> Result<(), Error> {
+        self.with(|inner| inner.set_mono(mono))?
+    }
+
+    /// Plays the audio on another device, see
+    /// [`Video::set_audio_device`](crate::video::Video::set_audio_device).
+    pub fn set_audio_device(&self, device: Option<AudioDevice>) -> Result<(), Error> {
+        self.with(|inner| inner.set_audio_device(device))?
+    }
+
+    /// Plays with a trick mode, see [`Video::set_trick_mode`](crate::video::Video::set_trick_mode).
+    pub fn set_trick_mode(&self, rate: f64, mode: TrickMode) -> Result<TrickMode, Error> {
+        self.with(|inner| inner.set_trick_mode(rate, mode))?
+    }
+
+    /// Get the current playback speed.
+    pub fn speed(&self) -> Result<f64, Error> {
+        self.read(|inner| inner.speed.get())
+    }
+
+    /// Get the current playback position in time.
+    pub fn position(&self) -> Result<Duration, Error> {
+        self.read(|inner| inner.position())
+    }
+
+    /// Get the media duration.
+    pub fn duration(&self) -> Result<Duration, Error> {
+        self.read(|inner| inner.duration.get())
+    }
+
+    /// Steps frames forward or backward, see [`Video::step_frames`](crate::video::Video::step_frames).
+    pub fn step_frames(&self, frames: i64) -> Result<(), Error> {
+        if frames != 0 && !self.paused()? {
+            self.set_paused(true)?;
+        }
+        self.with(|inner| inner.step_frames(frames))?
+    }
+
+    /// Jumps to the start of the chapter with the given index.
+    pub fn seek_to_chapter(&self, index: usize) -> Result<(), Error> {
+        self.with(|inner| inner.seek_to_chapter(index))?
+    }
+
+    /// Jumps to the start of the next chapter.
+    pub fn next_chapter(&self) -> Result<(), Error> {
+        self.with(|inner| inner.next_chapter())?
+    }
+
+    /// Jumps to the start of the current or previous chapter, see
+    /// [`Video::previous_chapter`](crate::video::Video::previous_chapter).
+    pub fn previous_chapter(&self) -> Result<(), Error> {
+        self.with(|inner| inner.previous_chapter())?
+    }
+
+    /// Restarts a stream; seeks to the first frame and unpauses, sets the `eos` flag to false.
+    pub fn restart_stream(&self) -> Result<(), Error> {
+        self.read(|inner| inner.is_eos.clone())?.set(false);
+        self.set_paused(false)?;
+        self.read(|inner| inner.seek(0, false))?
+    }
+}
@@ -1,8 +1,17 @@
 use gstreamer as gst;
 use thiserror::Error;
 
+pub mod audio;
+pub mod chapter;
+pub mod color;
+pub mod handle;
+pub mod metadata;
+pub mod orientation;
 pub mod pipeline;
 pub mod player;
+pub mod queue;
+pub mod subtitle;
+pub mod track;
 pub mod video;
 
 #[derive(Debug, Error)]
@@ -31,6 +40,16 @@ pub enum Error {
     Sync,
     #[error("failed to lock internal sync primitive")]
     Lock,
+    #[error("no track with index {0}")]
+    TrackIndex(usize),
+    #[error("no chapter with index {0}")]
+    ChapterIndex(usize),
+    #[error("not supported by this pipeline")]
+    Unsupported,
+    #[error("the video was dropped")]
+    VideoDropped,
+    #[error("timed out waiting for a decoded frame")]
+    FrameTimeout,
     #[error("invalid framerate: {0}")]
     Framerate(f64),
 }
@@ -0,0 +1,170 @@
+use glib::translate::IntoGlib;
+use gstreamer as gst;
+
+/// An image embedded in the media, e.g. an album cover.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverArt {
+    /// Mime type of the encoded image, e.g. `image/jpeg`.
+    pub mime: String,
+    /// The encoded image data.
+    pub data: Vec<u8>,
+}
+
+/// Metadata of the media, collected from the tags found in the stream.
+///
+/// Tags may arrive at any point during playback, see [`Video::metadata_reader`](crate::video::Video::metadata_reader).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaMetadata {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    /// Release date in ISO 8601 format, as precise as known.
+    pub date: Option<String>,
+    pub video_codec: Option<String>,
+    pub audio_codec: Option<String>,
+    pub subtitle_codec: Option<String>,
+    pub container: Option<String>,
+    /// Bitrate of the selected video stream in bits per second, see [`Track::bitrate`](crate::track::Track::bitrate).
+    pub video_bitrate: Option<u32>,
+    /// Bitrate of the selected audio stream in bits per second, see [`Track::bitrate`](crate::track::Track::bitrate).
+    pub audio_bitrate: Option<u32>,
+    /// ISO 639 language code.
+    pub language: Option<String>,
+    pub cover_art: Option<CoverArt>,
+    /// All tags merged together, for anything not covered by the fields above.
+    /// The bitrates are left out, as they differ per stream.
+    pub tags: gst::TagList,
+}
+
+impl Default for MediaMetadata {
+    fn default() -> Self {
+        Self::from_tags(gst::TagList::new())
+    }
+}
+
+impl MediaMetadata {
+    pub(crate) fn from_tags(tags: gst::TagList) -> Self {
+        MediaMetadata {
+            title: string::<gst::tags::Title>(&tags),
+            artist: string::<gst::tags::Artist>(&tags),
+            album: string::<gst::tags::Album>(&tags),
+            date: date(&tags),
+            video_codec: string::<gst::tags::VideoCodec>(&tags),
+            audio_codec: string::<gst::tags::AudioCodec>(&tags),
+            subtitle_codec: string::<gst::tags::SubtitleCodec>(&tags),
+            container: string::<gst::tags::ContainerFormat>(&tags),
+            // the merged tags mix up the streams, see `MediaMetadata::set_stream_bitrates`
+            video_bitrate: None,
+            audio_bitrate: None,
+            language: string::<gst::tags::LanguageCode>(&tags),
+            cover_art: cover_art(&tags),
+            tags,
+        }
+    }
+
+    /// Checks if merging `tags` changes anything.
+    pub(crate) fn is_changed_by(&self, tags: &gst::TagList) -> bool {
+        self.tags.merge(tags, gst::TagMergeMode::Replace) != self.tags
+    }
+
+    /// Merges newly found tags, replacing previous values of the same tags.
+    /// Only the fields of tags found in `tags` are read again.
+    pub(crate) fn merge(&mut self, tags: &gst::TagList) {
+        fn replace<T>(field: &mut Option<String>, tags: &gst::TagList)
+        where
+            T: for<'a> gst::tags::Tag<'a, TagType = &'a str>,
+        {
+            if let Some(value) = string::<T>(tags) {
+                *field = Some(value);
+            }
+        }
+
+        let merged = self.tags.merge(tags, gst::TagMergeMode::Replace);
+        replace::<gst::tags::Title>(&mut self.title, tags);
+        replace::<gst::tags::Artist>(&mut self.artist, tags);
+        replace::<gst::tags::Album>(&mut self.album, tags);
+        replace::<gst::tags::VideoCodec>(&mut self.video_codec, tags);
+        replace::<gst::tags::AudioCodec>(&mut self.audio_codec, tags);
+        replace::<gst::tags::SubtitleCodec>(&mut self.subtitle_codec, tags);
+        replace::<gst::tags::ContainerFormat>(&mut self.container, tags);
+        replace::<gst::tags::LanguageCode>(&mut self.language, tags);
+        // a new `Date` doesn't replace a previous, more precise `DateTime`
+        if tags.get::<gst::tags::DateTime>().is_some() || tags.get::<gst::tags::Date>().is_some() {
+            self.date = date(&merged);
+        }
+        // copying the image is expensive, so it's only read when it changed
+        if tags.get::<gst::tags::Image>().is_some()
+            || tags.get::<gst::tags::PreviewImage>().is_some()
+        {
+            self.cover_art = cover_art(&merged);
+        }
+        self.tags = merged;
+    }
+
+    /// Sets the bitrates from the tags of the selected video and audio streams.
+    pub(crate) fn set_stream_bitrates(
+        &mut self,
+        video: Option<&gst::TagList>,
+        audio: Option<&gst::TagList>,
+    ) {
+        self.video_bitrate = video.and_then(bitrate);
+        self.audio_bitrate = audio.and_then(bitrate);
+    }
+}
+
+fn string<T>(tags: &gst::TagList) -> Option<String>
+where
+    T: for<'a> gst::tags::Tag<'a, TagType = &'a str>,
+{
+    tags.get::<T>().map(|value| value.get().to_string())
+}
+
+fn date(tags: &gst::TagList) -> Option<String> {
+    tags.get::<gst::tags::DateTime>()
+        .and_then(|value| value.get().to_iso8601_string().ok())
+        .map(|date| date.to_string())
+        .or_else(|| {
+            tags.get::<gst::tags::Date>().map(|value| {
+                let date = value.get();
+                format!(
+                    "{:04}-{:02}-{:02}",
+                    date.year(),
+                    date.month().into_glib(),
+                    date.day()
+                )
+            })
+        })
+}
+
+fn cover_art(tags: &gst::TagList) -> Option<CoverArt> {
+    tags.get::<gst::tags::Image>()
+        .or_else(|| tags.get::<gst::tags::PreviewImage>())
+        .and_then(|value| {
+            let sample = value.get();
+            let mime = sample.caps()?.structure(0)?.name().to_string();
+            let map = sample.buffer()?.map_readable().ok()?;
+            Some(CoverArt {
+                mime,
+                data: map.as_slice().to_vec(),
+            })
+        })
+}
+
+/// Copies `tags` without the bitrates, which change every few buffers and are
+/// read per stream instead.
+pub(crate) fn without_bitrates(tags: &gst::TagList) -> gst::TagList {
+    let mut tags = tags.copy();
+    let tags_mut = tags.make_mut();
+    tags_mut.remove::<gst::tags::Bitrate>();
+    tags_mut.remove::<gst::tags::NominalBitrate>();
+    tags_mut.remove::<gst::tags::MinimumBitrate>();
+    tags_mut.remove::<gst::tags::MaximumBitrate>();
+    tags
+}
+
+/// Reads the exact or average bitrate of a stream, or its nominal bitrate if unknown.
+pub(crate) fn bitrate(tags: &gst::TagList) -> Option<u32> {
+    tags.get::<gst::tags::Bitrate>()
+        .or_else(|| tags.get::<gst::tags::NominalBitrate>())
+        .map(|value| value.get())
+}
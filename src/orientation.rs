@@ -0,0 +1,230 @@
+use gstreamer as gst;
+
+/// How the video has to be turned to be shown upright, named after the values of the
+/// `image-orientation` tag. The flipped variants are mirrored horizontally before rotating.
+///
+/// Phones record portrait video as landscape frames with this tag, see
+/// [`Video::orientation`](crate::video::Video::orientation).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Orientation {
+    #[default]
+    Rotate0,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipRotate0,
+    FlipRotate90,
+    FlipRotate180,
+    FlipRotate270,
+}
+
+impl Orientation {
+    /// Reads the `image-orientation` tag, `None` if it's missing or unknown.
+    pub(crate) fn from_tags(tags: &gst::TagList) -> Option<Self> {
+        let orientation = match tags.get::<gst::tags::ImageOrientation>()?.get() {
+            "rotate-0" => Orientation::Rotate0,
+            "rotate-90" => Orientation::Rotate90,
+            "rotate-180" => Orientation::Rotate180,
+            "rotate-270" => Orientation::Rotate270,
+            "flip-rotate-0" => Orientation::FlipRotate0,
+            "flip-rotate-90" => Orientation::FlipRotate90,
+            "flip-rotate-180" => Orientation::FlipRotate180,
+            "flip-rotate-270" => Orientation::FlipRotate270,
+            other => {
+                log::warn!("unknown image orientation {other}");
+                return None;
+            }
+        };
+        Some(orientation)
+    }
+
+    fn from_parts(flip: bool, rotation: u32) -> Self {
+        match (flip, rotation % 360) {
+            (false, 0) => Orientation::Rotate0,
+            (false, 90) => Orientation::Rotate90,
+            (false, 180) => Orientation::Rotate180,
+            (false, 270) => Orientation::Rotate270,
+            (true, 0) => Orientation::FlipRotate0,
+            (true, 90) => Orientation::FlipRotate90,
+            (true, 180) => Orientation::FlipRotate180,
+            (true, 270) => Orientation::FlipRotate270,
+            _ => unreachable!("rotation is a multiple of 90 degrees"),
+        }
+    }
+
+    /// If the video is mirrored, and the clockwise rotation in degrees applied afterwards.
+    fn parts(self) -> (bool, u32) {
+        match self {
+            Orientation::Rotate0 => (false, 0),
+            Orientation::Rotate90 => (false, 90),
+            Orientation::Rotate180 => (false, 180),
+            Orientation::Rotate270 => (false, 270),
+            Orientation::FlipRotate0 => (true, 0),
+            Orientation::FlipRotate90 => (true, 90),
+            Orientation::FlipRotate180 => (true, 180),
+            Orientation::FlipRotate270 => (true, 270),
+        }
+    }
+
+    /// Turns the picture by another 90 degrees clockwise.
+    #[must_use]
+    pub fn rotated_clockwise(self) -> Self {
+        let (flip, rotation) = self.parts();
+        Self::from_parts(flip, rotation + 90)
+    }
+
+    /// Turns the picture by another 90 degrees counterclockwise.
+    #[must_use]
+    pub fn rotated_counterclockwise(self) -> Self {
+        let (flip, rotation) = self.parts();
+        Self::from_parts(flip, rotation + 270)
+    }
+
+    /// Mirrors the picture left to right, as shown after this orientation.
+    #[must_use]
+    pub fn flipped_horizontally(self) -> Self {
+        let (flip, rotation) = self.parts();
+        // mirroring after a rotation is the same as mirroring before the opposite rotation
+        Self::from_parts(!flip, 360 - rotation)
+    }
+
+    /// Mirrors the picture top to bottom, as shown after this orientation.
+    #[must_use]
+    pub fn flipped_vertically(self) -> Self {
+        self.flipped_horizontally()
+            .rotated_clockwise()
+            .rotated_clockwise()
+    }
+
+    /// Checks if the picture is turned sideways, so its width and height are swapped.
+    #[must_use]
+    pub fn is_sideways(self) -> bool {
+        matches!(self.parts(), (_, 90 | 270))
+    }
+
+    /// Columns of the matrix mapping a point on screen to the texture, both relative
+    /// to the center, in the WGSL `mat2x2` layout.
+    pub(crate) fn texture_matrix(self) -> [[f32; 2]; 2] {
+        let (flip, rotation) = self.parts();
+        // inverse of the clockwise rotation, as the shader looks up the texture from the screen
+        let mut columns = match rotation {
+            90 => [[0.0, -1.0], [1.0, 0.0]],
+            180 => [[-1.0, 0.0], [0.0, -1.0]],
+            270 => [[0.0, 1.0], [-1.0, 0.0]],
+            _ => [[1.0, 0.0], [0.0, 1.0]],
+        };
+        if flip {
+            for column in &mut columns {
+                column[0] = -column[0];
+            }
+        }
+        columns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Orientation::*;
+
+    const ALL: [Orientation; 8] = [
+        Rotate0,
+        Rotate90,
+        Rotate180,
+        Rotate270,
+        FlipRotate0,
+        FlipRotate90,
+        FlipRotate180,
+        FlipRotate270,
+    ];
+
+    #[test]
+    fn transformations() {
+        // orientation, rotated clockwise, rotated counterclockwise, flipped horizontally and vertically
+        let cases = [
+            (Rotate0, Rotate90, Rotate270, FlipRotate0, FlipRotate180),
+            (Rotate90, Rotate180, Rotate0, FlipRotate270, FlipRotate90),
+            (Rotate180, Rotate270, Rotate90, FlipRotate180, FlipRotate0),
+            (Rotate270, Rotate0, Rotate180, FlipRotate90, FlipRotate270),
+            (FlipRotate0, FlipRotate90, FlipRotate270, Rotate0, Rotate180),
+            (
+                FlipRotate90,
+                FlipRotate180,
+                FlipRotate0,
+                Rotate270,
+                Rotate90,
+            ),
+            (
+                FlipRotate180,
+                FlipRotate270,
+                FlipRotate90,
+                Rotate180,
+                Rotate0,
+            ),
+            (
+                FlipRotate270,
+                FlipRotate0,
+                FlipRotate180,
+                Rotate90,
+                Rotate270,
+            ),
+        ];
+        for (orientation, clockwise, counterclockwise, horizontally, vertically) in cases {
+            assert_eq!(
+                orientation.rotated_clockwise(),
+                clockwise,
+                "{orientation:?}"
+            );
+            assert_eq!(
+                orientation.rotated_counterclockwise(),
+                counterclockwise,
+                "{orientation:?}"
+            );
+            assert_eq!(
+                orientation.flipped_horizontally(),
+                horizontally,
+                "{orientation:?}"
+            );
+            assert_eq!(
+                orientation.flipped_vertically(),
+                vertically,
+                "{orientation:?}"
+            );
+        }
+
+        for orientation in ALL {
+            let flipped = orientation.flipped_horizontally().flipped_horizontally();
+            assert_eq!(flipped, orientation);
+            let turned = orientation.rotated_clockwise().rotated_counterclockwise();
+            assert_eq!(turned, orientation);
+            assert_eq!(
+                orientation.rotated_clockwise().is_sideways(),
+                !orientation.is_sideways()
+            );
+        }
+    }
+
+    #[test]
+    fn texture_matrix() {
+        let cases = [
+            (Rotate0, [[1.0, 0.0], [0.0, 1.0]]),
+            (Rotate90, [[0.0, -1.0], [1.0, 0.0]]),
+            (Rotate180, [[-1.0, 0.0], [0.0, -1.0]]),
+            (Rotate270, [[0.0, 1.0], [-1.0, 0.0]]),
+            (FlipRotate0, [[-1.0, 0.0], [0.0, 1.0]]),
+            (FlipRotate90, [[0.0, -1.0], [-1.0, 0.0]]),
+            (FlipRotate180, [[1.0, 0.0], [0.0, -1.0]]),
+            (FlipRotate270, [[0.0, 1.0], [1.0, 0.0]]),
+        ];
+        for (orientation, expected) in cases {
+            assert_eq!(orientation.texture_matrix(), expected, "{orientation:?}");
+        }
+
+        // mirroring flips the handedness
+        for orientation in ALL {
+            let [[a, b], [c, d]] = orientation.texture_matrix();
+            let flipped = matches!(orientation.parts(), (true, _));
+            assert_eq!(a * d - b * c, if flipped { -1.0 } else { 1.0 });
+        }
+    }
+}
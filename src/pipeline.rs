@@ -1,8 +1,8 @@
 use std::{
-    collections::{btree_map::Entry, BTreeMap},
+    collections::BTreeMap,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc, Mutex,
+        Arc,
     },
 };
 
@@ -15,23 +15,151 @@ use cushy::{
     RenderOperation,
 };
 
+use crate::{
+    color::{self, Colorimetry, PictureAdjustments, ToneMapping, TransferFunction},
+    orientation::Orientation,
+    video::{Frame, FrameFormat, FrameSlot},
+};
+
 #[repr(C)]
 struct Uniforms {
     rect: [f32; 4],
+    yuv_matrix: [[f32; 4]; 3],
+    yuv_offset: [f32; 4],
+    gamut_matrix: [[f32; 4]; 3],
+    /// 0 for SDR, 1 for PQ and 2 for HLG.
+    transfer: u32,
+    /// Index of the [`ToneMapping`] variant.
+    tone_mapping: u32,
+    /// Peak luminance of the content relative to SDR white.
+    peak: f32,
+    gamma: f32,
+    brightness: f32,
+    contrast: f32,
+    saturation: f32,
+    /// Hue rotation in radians.
+    hue: f32,
+    /// Maps the screen to the texture, see [`Orientation::texture_matrix`].
+    orientation: [[f32; 2]; 2],
+}
+
+/// Texture layout of one plane of a [`Frame`].
+struct PlaneLayout {
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    /// The plane holds 16-bit samples, but only their high byte is uploaded,
+    /// as the device doesn't support 16-bit normalized textures.
+    truncate_16bit: bool,
+}
+
+impl PlaneLayout {
+    fn for_frame(
+        format: FrameFormat,
+        (width, height): (u32, u32),
+        features: wgpu::Features,
+    ) -> Vec<Self> {
+        let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+        let plane = |format, width, height| PlaneLayout {
+            format,
+            width,
+            height,
+            truncate_16bit: false,
+        };
+        match format {
+            FrameFormat::Nv12 => vec![
+                plane(wgpu::TextureFormat::R8Unorm, width, height),
+                plane(wgpu::TextureFormat::Rg8Unorm, chroma_width, chroma_height),
+            ],
+            FrameFormat::I420 => vec![
+                plane(wgpu::TextureFormat::R8Unorm, width, height),
+                plane(wgpu::TextureFormat::R8Unorm, chroma_width, chroma_height),
+                plane(wgpu::TextureFormat::R8Unorm, chroma_width, chroma_height),
+            ],
+            FrameFormat::P016 if features.contains(wgpu::Features::TEXTURE_FORMAT_16BIT_NORM) => {
+                vec![
+                    plane(wgpu::TextureFormat::R16Unorm, width, height),
+                    plane(wgpu::TextureFormat::Rg16Unorm, chroma_width, chroma_height),
+                ]
+            }
+            FrameFormat::P016 => vec![
+                PlaneLayout {
+                    truncate_16bit: true,
+                    ..plane(wgpu::TextureFormat::R8Unorm, width, height)
+                },
+                PlaneLayout {
+                    truncate_16bit: true,
+                    ..plane(wgpu::TextureFormat::Rg8Unorm, chroma_width, chroma_height)
+                },
+            ],
+            FrameFormat::Rgba => vec![plane(wgpu::TextureFormat::Rgba8Unorm, width, height)],
+            FrameFormat::Bgra => vec![plane(wgpu::TextureFormat::Bgra8Unorm, width, height)],
+        }
+    }
+
+    fn bytes_per_row(&self) -> u32 {
+        self.width * self.format.block_copy_size(None).unwrap_or(1)
+    }
+}
+
+/// Keeps the high byte of each little-endian 16-bit sample.
+fn truncate_16bit(data: &[u8], stride: u32, layout: &PlaneLayout) -> Vec<u8> {
+    let row_len = layout.bytes_per_row() as usize;
+    let mut out = Vec::with_capacity(row_len * layout.height as usize);
+    for row in data.chunks(stride as usize).take(layout.height as usize) {
+        out.extend(row.iter().skip(1).step_by(2).take(row_len));
+    }
+    out
 }
 
 struct VideoEntry {
-    texture_y: wgpu::Texture,
-    texture_uv: wgpu::Texture,
+    size: (u32, u32),
+    format: FrameFormat,
+    /// Colorimetry of the last uploaded frame.
+    colorimetry: Colorimetry,
+    /// Peak luminance of the last uploaded frame in cd/m², if known.
+    peak_luminance: Option<f32>,
+    textures: Vec<wgpu::Texture>,
     uniforms: wgpu::Buffer,
     bg0: wgpu::BindGroup,
     alive: Arc<AtomicBool>,
 }
 
+impl VideoEntry {
+    fn destroy(self) {
+        for texture in self.textures {
+            texture.destroy();
+        }
+        self.uniforms.destroy();
+    }
+}
+
+/// Pipelines for the fragment shader variants, see `shader.wgsl`.
+struct Pipelines {
+    /// Y plane and interleaved UV plane.
+    semi_planar: wgpu::RenderPipeline,
+    /// Separate Y, U and V planes.
+    planar: wgpu::RenderPipeline,
+    /// A single RGB plane.
+    packed: wgpu::RenderPipeline,
+}
+
+impl Pipelines {
+    fn for_format(&self, format: FrameFormat) -> &wgpu::RenderPipeline {
+        match format {
+            FrameFormat::Nv12 | FrameFormat::P016 => &self.semi_planar,
+            FrameFormat::I420 => &self.planar,
+            FrameFormat::Rgba | FrameFormat::Bgra => &self.packed,
+        }
+    }
+}
+
 struct VideoPipeline {
-    pipeline: wgpu::RenderPipeline,
+    pipelines: Pipelines,
     bg0_layout: wgpu::BindGroupLayout,
     sampler: wgpu::Sampler,
+    /// Bound in place of planes the frame format doesn't have.
+    empty_plane: wgpu::TextureView,
     videos: BTreeMap<u64, VideoEntry>,
 }
 
@@ -43,29 +171,22 @@ impl VideoPipeline {
             source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
         });
 
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+
         let bg0_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("iced_video_player bind group 0 layout"),
             entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        multisampled: false,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        multisampled: false,
-                    },
-                    count: None,
-                },
+                texture_entry(0),
+                texture_entry(1),
                 wgpu::BindGroupLayoutEntry {
                     binding: 2,
                     visibility: wgpu::ShaderStages::FRAGMENT,
@@ -74,7 +195,7 @@ impl VideoPipeline {
                 },
                 wgpu::BindGroupLayoutEntry {
                     binding: 3,
-                    visibility: wgpu::ShaderStages::VERTEX,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
@@ -82,6 +203,7 @@ impl VideoPipeline {
                     },
                     count: None,
                 },
+                texture_entry(4),
             ],
         });
 
@@ -91,31 +213,38 @@ impl VideoPipeline {
             push_constant_ranges: &[],
         });
 
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("iced_video_player pipeline"),
-            layout: Some(&layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[],
-                compilation_options: Default::default(),
-            },
-            primitive: wgpu::PrimitiveState::default(),
-            depth_stencil: None,
-            multisample: graphics.multisample_state(),
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: graphics.texture_format(),
-                    blend: None,
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: Default::default(),
-            }),
-            multiview: None,
-            cache: None,
-        });
+        let create_pipeline = |entry_point| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("iced_video_player pipeline"),
+                layout: Some(&layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                    compilation_options: Default::default(),
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: graphics.multisample_state(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some(entry_point),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: graphics.texture_format(),
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                multiview: None,
+                cache: None,
+            })
+        };
+        let pipelines = Pipelines {
+            semi_planar: create_pipeline("fs_main"),
+            planar: create_pipeline("fs_planar"),
+            packed: create_pipeline("fs_packed"),
+        };
 
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             label: Some("iced_video_player sampler"),
@@ -132,164 +261,186 @@ impl VideoPipeline {
             border_color: None,
         });
 
+        let empty_plane = device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("iced_video_player empty texture"),
+                size: wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::R8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            })
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
         VideoPipeline {
-            pipeline,
+            pipelines,
             bg0_layout,
             sampler,
+            empty_plane,
             videos: BTreeMap::new(),
         }
     }
 
-    fn upload(
-        &mut self,
+    fn create_entry(
+        &self,
         device: &wgpu::Device,
-        queue: &wgpu::Queue,
-        video_id: u64,
         alive: &Arc<AtomicBool>,
         (width, height): (u32, u32),
-        frame: &[u8],
-    ) {
-        if let Entry::Vacant(entry) = self.videos.entry(video_id) {
-            let texture_y = device.create_texture(&wgpu::TextureDescriptor {
-                label: Some("iced_video_player texture"),
-                size: wgpu::Extent3d {
-                    width,
-                    height,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::R8Unorm,
-                usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
-                view_formats: &[],
-            });
+        format: FrameFormat,
+    ) -> VideoEntry {
+        let textures: Vec<_> = PlaneLayout::for_frame(format, (width, height), device.features())
+            .into_iter()
+            .map(|plane| {
+                device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some("iced_video_player texture"),
+                    size: wgpu::Extent3d {
+                        width: plane.width,
+                        height: plane.height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: plane.format,
+                    usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                })
+            })
+            .collect();
 
-            let texture_uv = device.create_texture(&wgpu::TextureDescriptor {
-                label: Some("iced_video_player texture"),
-                size: wgpu::Extent3d {
-                    width: width / 2,
-                    height: height / 2,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rg8Unorm,
-                usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
-                view_formats: &[],
-            });
+        let views: Vec<_> = textures
+            .iter()
+            .map(|texture| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("iced_video_player texture view"),
+                    format: None,
+                    dimension: None,
+                    aspect: wgpu::TextureAspect::All,
+                    base_mip_level: 0,
+                    mip_level_count: None,
+                    base_array_layer: 0,
+                    array_layer_count: None,
+                })
+            })
+            .collect();
+        let view = |plane: usize| views.get(plane).unwrap_or(&self.empty_plane);
 
-            let view_y = texture_y.create_view(&wgpu::TextureViewDescriptor {
-                label: Some("iced_video_player texture view"),
-                format: None,
-                dimension: None,
-                aspect: wgpu::TextureAspect::All,
-                base_mip_level: 0,
-                mip_level_count: None,
-                base_array_layer: 0,
-                array_layer_count: None,
-            });
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("iced_video_player uniform buffer"),
+            size: std::mem::size_of::<Uniforms>() as _,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
 
-            let view_uv = texture_uv.create_view(&wgpu::TextureViewDescriptor {
-                label: Some("iced_video_player texture view"),
-                format: None,
-                dimension: None,
-                aspect: wgpu::TextureAspect::All,
-                base_mip_level: 0,
-                mip_level_count: None,
-                base_array_layer: 0,
-                array_layer_count: None,
-            });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("iced_video_player bind group"),
+            layout: &self.bg0_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view(0)),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(view(1)),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &buffer,
+                        offset: 0,
+                        size: None,
+                    }),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::TextureView(view(2)),
+                },
+            ],
+        });
 
-            let buffer = device.create_buffer(&wgpu::BufferDescriptor {
-                label: Some("iced_video_player uniform buffer"),
-                size: std::mem::size_of::<Uniforms>() as _,
-                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
-                mapped_at_creation: false,
-            });
+        VideoEntry {
+            size: (width, height),
+            format,
+            colorimetry: Colorimetry::default(),
+            peak_luminance: None,
+            textures,
+            uniforms: buffer,
+            bg0: bind_group,
+            alive: Arc::clone(alive),
+        }
+    }
 
-            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("iced_video_player bind group"),
-                layout: &self.bg0_layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureView(&view_y),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::TextureView(&view_uv),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 2,
-                        resource: wgpu::BindingResource::Sampler(&self.sampler),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 3,
-                        resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-                            buffer: &buffer,
-                            offset: 0,
-                            size: None,
-                        }),
-                    },
-                ],
-            });
+    fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        video_id: u64,
+        alive: &Arc<AtomicBool>,
+        frame: &Frame,
+    ) {
+        let (width, height) = (frame.width(), frame.height());
+        let layouts = PlaneLayout::for_frame(frame.format, (width, height), device.features());
+        if frame.n_planes() != layouts.len() {
+            return;
+        }
 
-            entry.insert(VideoEntry {
-                texture_y,
-                texture_uv,
-                uniforms: buffer,
-                bg0: bind_group,
-                alive: Arc::clone(alive),
-            });
+        // the resolution or format can change mid-stream, which needs new textures
+        let stale = self.videos.get(&video_id).map_or(true, |entry| {
+            entry.size != (width, height) || entry.format != frame.format
+        });
+        if stale {
+            if let Some(entry) = self.videos.remove(&video_id) {
+                entry.destroy();
+            }
+            let entry = self.create_entry(device, alive, (width, height), frame.format);
+            self.videos.insert(video_id, entry);
         }
 
-        let VideoEntry {
-            texture_y,
-            texture_uv,
-            ..
-        } = self.videos.get(&video_id).unwrap();
-
-        queue.write_texture(
-            wgpu::ImageCopyTexture {
-                texture: texture_y,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            &frame[..(width * height) as usize],
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(width),
-                rows_per_image: Some(height),
-            },
-            wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-        );
-
-        queue.write_texture(
-            wgpu::ImageCopyTexture {
-                texture: texture_uv,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            &frame[(width * height) as usize..],
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(width),
-                rows_per_image: Some(height / 2),
-            },
-            wgpu::Extent3d {
-                width: width / 2,
-                height: height / 2,
-                depth_or_array_layers: 1,
-            },
-        );
+        let entry = self.videos.get_mut(&video_id).unwrap();
+        entry.colorimetry = frame.colorimetry;
+        entry.peak_luminance = frame.peak_luminance;
+        for ((texture, layout), (data, stride)) in
+            entry.textures.iter().zip(&layouts).zip(frame.planes())
+        {
+            let truncated;
+            let (data, stride) = if layout.truncate_16bit {
+                truncated = truncate_16bit(data, stride, layout);
+                (truncated.as_slice(), layout.bytes_per_row())
+            } else {
+                (data, stride)
+            };
+
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                data,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(stride),
+                    rows_per_image: Some(layout.height),
+                },
+                wgpu::Extent3d {
+                    width: layout.width,
+                    height: layout.height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
     }
 
     fn cleanup(&mut self) {
@@ -300,15 +451,32 @@ impl VideoPipeline {
             .collect();
         for id in ids {
             if let Some(video) = self.videos.remove(&id) {
-                video.texture_y.destroy();
-                video.texture_uv.destroy();
-                video.uniforms.destroy();
+                video.destroy();
             }
         }
     }
 
-    fn prepare(&mut self, queue: &wgpu::Queue, video_id: u64, bounds: Rect<Px>) {
-        if let Some(video) = self.videos.get(&video_id) {
+    fn prepare(&mut self, queue: &wgpu::Queue, bounds: Rect<Px>, primitive: &VideoPrimitive) {
+        if let Some(video) = self.videos.get(&primitive.video_id) {
+            let RenderParams {
+                colorimetry_override,
+                tone_mapping,
+                adjustments,
+                orientation,
+            } = primitive.params;
+            let colorimetry = colorimetry_override.unwrap_or(video.colorimetry);
+            let transfer = match colorimetry.transfer {
+                TransferFunction::Bt709 | TransferFunction::Srgb => 0,
+                TransferFunction::Pq => 1,
+                TransferFunction::Hlg => 2,
+            };
+            let tone_mapping = match tone_mapping {
+                ToneMapping::Clip => 0,
+                ToneMapping::Reinhard => 1,
+                ToneMapping::Hable => 2,
+                ToneMapping::Bt2390 => 3,
+            };
+            let peak = video.peak_luminance.unwrap_or(color::DEFAULT_HDR_PEAK) / color::SDR_WHITE;
             let uniforms = Uniforms {
                 rect: [
                     bounds.origin.x.into(),
@@ -316,6 +484,18 @@ impl VideoPipeline {
                     (bounds.origin.x + bounds.size.width).into(),
                     (bounds.origin.y + bounds.size.height).into(),
                 ],
+                yuv_matrix: colorimetry.yuv_matrix(),
+                yuv_offset: colorimetry.yuv_offset(),
+                gamut_matrix: colorimetry.gamut_matrix(),
+                transfer,
+                tone_mapping,
+                peak,
+                gamma: adjustments.gamma.max(0.01),
+                brightness: adjustments.brightness,
+                contrast: adjustments.contrast.max(0.0),
+                saturation: adjustments.saturation.max(0.0),
+                hue: adjustments.hue.to_radians(),
+                orientation: orientation.texture_matrix(),
             };
             queue.write_buffer(&video.uniforms, 0, unsafe {
                 std::slice::from_raw_parts(
@@ -330,7 +510,7 @@ impl VideoPipeline {
 
     fn draw(&self, pass: &mut wgpu::RenderPass, viewport: Rect<UPx>, video_id: u64) {
         if let Some(video) = self.videos.get(&video_id) {
-            pass.set_pipeline(&self.pipeline);
+            pass.set_pipeline(self.pipelines.for_format(video.format));
             pass.set_bind_group(0, &video.bg0, &[]);
             pass.set_viewport(
                 viewport.origin.x.get() as _,
@@ -366,17 +546,18 @@ impl RenderOperation for VideoRO {
         graphics: &mut cushy::kludgine::Graphics<'_>,
     ) -> Self::Prepared {
         if context.upload_frame {
-            self.pipeline.upload(
-                graphics.device(),
-                graphics.queue(),
-                context.video_id,
-                &context.alive,
-                context.size,
-                context.frame.lock().expect("lock frame mutex").as_slice(),
-            );
+            // uploads straight from the mapped decoder buffer, without blocking the decoder thread
+            if let Some(frame) = context.frame.load_full() {
+                self.pipeline.upload(
+                    graphics.device(),
+                    graphics.queue(),
+                    context.video_id,
+                    &context.alive,
+                    &frame,
+                );
+            }
         }
-        self.pipeline
-            .prepare(graphics.queue(), context.video_id, rect);
+        self.pipeline.prepare(graphics.queue(), rect, &context);
         context
     }
 
@@ -398,25 +579,35 @@ impl RenderOperation for VideoRO {
 pub(crate) struct VideoPrimitive {
     video_id: u64,
     alive: Arc<AtomicBool>,
-    frame: Arc<Mutex<Vec<u8>>>,
-    size: (u32, u32),
+    frame: Arc<FrameSlot>,
     upload_frame: bool,
+    params: RenderParams,
 }
 
 impl VideoPrimitive {
     pub fn new(
         video_id: u64,
         alive: Arc<AtomicBool>,
-        frame: Arc<Mutex<Vec<u8>>>,
-        size: (u32, u32),
+        frame: Arc<FrameSlot>,
         upload_frame: bool,
+        params: RenderParams,
     ) -> Self {
         VideoPrimitive {
             video_id,
             alive,
             frame,
-            size,
             upload_frame,
+            params,
         }
     }
 }
+
+/// How the frame of a [`VideoPrimitive`] is converted and placed.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RenderParams {
+    /// Replaces the colorimetry read from the stream, if set.
+    pub(crate) colorimetry_override: Option<Colorimetry>,
+    pub(crate) tone_mapping: ToneMapping,
+    pub(crate) adjustments: PictureAdjustments,
+    pub(crate) orientation: Orientation,
+}
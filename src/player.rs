@@ -1,6 +1,6 @@
 use std::{
+    future::Future,
     sync::{atomic::Ordering, Arc},
-    time::Instant,
 };
 
 use cushy::{
@@ -9,40 +9,57 @@ use cushy::{
         units::{Px, UPx},
         FloatConversion, IntoSigned, IntoUnsigned, Point, Rect, Size,
     },
-    value::{Destination, Dynamic, DynamicReader, Generation, Source, Value},
-    widget::Widget,
-    widgets::image::{Aspect, ImageScaling},
+    value::{Destination, Dynamic, DynamicReader, IntoValue, Source, Value},
+    widget::{MakeWidget, Widget, WidgetInstance},
+    widgets::{
+        image::{Aspect, ImageScaling},
+        Switcher,
+    },
     ConstraintLimit,
 };
 
 use crate::{
-    pipeline::{VideoPrimitive, VideoRO},
-    video::{Internal, Video},
+    color::{PictureAdjustments, ToneMapping},
+    pipeline::{RenderParams, VideoPrimitive, VideoRO},
+    subtitle::SubtitleCue,
+    video::{Internal, PlayerState, Video, VideoEvent, VideoHandle},
     Error,
 };
 
 /// A video player widget, with no builtin controls.
 /// Autoplays by default.
 /// Supports subtitles, but doesn't render them - see [VideoPlayer::get_subtitles]
+/// and [`SubtitleCue::to_widget`]
 #[derive(Debug)]
 pub struct VideoPlayer {
     video: Video,
-    subtitles: Dynamic<Option<String>>,
+    subtitles: Dynamic<Option<SubtitleCue>>,
     frame: Dynamic<()>,
-    last_frame: Generation,
     scaling: Value<ImageScaling>,
+    tone_mapping: Value<ToneMapping>,
+    brightness: Value<f32>,
+    contrast: Value<f32>,
+    saturation: Value<f32>,
+    hue: Value<f32>,
+    gamma: Value<f32>,
 }
 
 impl VideoPlayer {
     pub fn new(video: Video) -> Self {
         let subtitles = video.0.read().unwrap().subtitles.clone();
         let frame = video.0.read().unwrap().upload_frame.clone();
+        let adjustments = PictureAdjustments::default();
         Self {
             subtitles,
             video,
-            last_frame: Generation::default(),
             frame,
             scaling: Default::default(),
+            tone_mapping: Default::default(),
+            brightness: Value::Constant(adjustments.brightness),
+            contrast: Value::Constant(adjustments.contrast),
+            saturation: Value::Constant(adjustments.saturation),
+            hue: Value::Constant(adjustments.hue),
+            gamma: Value::Constant(adjustments.gamma),
         }
     }
 
@@ -50,10 +67,90 @@ impl VideoPlayer {
         Ok(Self::new(Video::new(url)?))
     }
 
+    /// Creates a widget that shows `placeholder` while `video` loads, e.g. from [`Video::new_async`].
+    /// Once loaded, the player (or the error) is passed to `build`, and the returned widget replaces the placeholder.
+    ///
+    /// The video is loaded on the current tokio runtime, or on a background thread if there is none.
+    pub fn loading<F, B>(video: F, placeholder: impl MakeWidget, build: B) -> Switcher
+    where
+        F: Future<Output = Result<Video, Error>> + Send + 'static,
+        B: FnOnce(Result<VideoPlayer, Error>) -> WidgetInstance + Send + 'static,
+    {
+        let widget = Dynamic::new(placeholder.make_widget());
+        let loaded = widget.clone();
+        let task = async move {
+            let player = video.await.map(VideoPlayer::new);
+            loaded.set(build(player));
+        };
+
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                runtime.spawn(task);
+            }
+            Err(_) => {
+                std::thread::spawn(move || {
+                    tokio::runtime::Builder::new_current_thread()
+                        .build()
+                        .expect("failed to create a runtime for loading the video")
+                        .block_on(task);
+                });
+            }
+        }
+
+        Switcher::new(widget)
+    }
+
+    /// Sets how HDR video is mapped to the SDR range of the display.
+    /// Has no effect on SDR video. Defaults to [`ToneMapping::Bt2390`].
+    #[must_use]
+    pub fn tone_mapping(mut self, tone_mapping: impl IntoValue<ToneMapping>) -> Self {
+        self.tone_mapping = tone_mapping.into_value();
+        self
+    }
+
+    /// Sets the brightness, added to the picture. `0.0` by default, `-1.0` is black and `1.0` is white.
+    ///
+    /// The picture adjustments are applied while drawing, so they are cheap to change,
+    /// e.g. from a slider bound to a [`Dynamic`].
+    #[must_use]
+    pub fn brightness(mut self, brightness: impl IntoValue<f32>) -> Self {
+        self.brightness = brightness.into_value();
+        self
+    }
+
+    /// Sets the contrast. `1.0` by default, `0.0` is flat grey.
+    #[must_use]
+    pub fn contrast(mut self, contrast: impl IntoValue<f32>) -> Self {
+        self.contrast = contrast.into_value();
+        self
+    }
+
+    /// Sets the color saturation. `1.0` by default, `0.0` is greyscale.
+    #[must_use]
+    pub fn saturation(mut self, saturation: impl IntoValue<f32>) -> Self {
+        self.saturation = saturation.into_value();
+        self
+    }
+
+    /// Sets the hue rotation in degrees, `0.0` by default.
+    #[must_use]
+    pub fn hue(mut self, hue: impl IntoValue<f32>) -> Self {
+        self.hue = hue.into_value();
+        self
+    }
+
+    /// Sets the gamma. `1.0` by default, higher values brighten the midtones.
+    #[must_use]
+    pub fn gamma(mut self, gamma: impl IntoValue<f32>) -> Self {
+        self.gamma = gamma.into_value();
+        self
+    }
+
     /// Returns a dynamic source that can be used to get the subtitles, if present.
-    /// Currently, HTML entities are unescaped, but no other processing is done. No rich text support.
+    /// The formatting of the stream (italic, bold, colors, ...) is kept in styled spans,
+    /// see [`SubtitleCue::to_widget`] to show them.
     #[must_use]
-    pub fn get_subtitles(&self) -> DynamicReader<Option<String>> {
+    pub fn get_subtitles(&self) -> DynamicReader<Option<SubtitleCue>> {
         self.subtitles.clone().into_reader()
     }
 
@@ -63,6 +160,24 @@ impl VideoPlayer {
         self.frame.clone().into_reader()
     }
 
+    /// Gets a dynamic reader of the playback events, see [`Video::events`].
+    #[must_use]
+    pub fn events(&self) -> DynamicReader<Option<VideoEvent>> {
+        self.video.events()
+    }
+
+    /// Gets a dynamic reader of the playback state, see [`Video::state`].
+    #[must_use]
+    pub fn state(&self) -> DynamicReader<PlayerState> {
+        self.video.state()
+    }
+
+    /// Gets a cloneable handle that can control playback, see [`Video::handle`].
+    #[must_use]
+    pub fn handle(&self) -> VideoHandle {
+        self.video.handle()
+    }
+
     /// Gets a read handle on the inner Video object. Can be used to control playback and get metadata.
     pub fn video(&self) -> &Video {
         &self.video
@@ -75,9 +190,21 @@ impl VideoPlayer {
         context: &mut GraphicsContext<'_, '_, '_, '_>,
     ) -> Rect<Px> {
         let within_size = within_size.into_signed();
+        // re-runs layout when the resolution changes mid-stream
+        let (width, height) = video.size.get_tracking_invalidate(context);
+        let orientation = video
+            .orientation_override
+            .get_tracking_invalidate(context)
+            .unwrap_or_else(|| video.orientation.get_tracking_invalidate(context));
+        // the picture is shown turned sideways, so it takes up the space the other way around
+        let (width, height) = if orientation.is_sideways() {
+            (height, width)
+        } else {
+            (width, height)
+        };
         let size = Size {
-            width: Px::new(video.width),
-            height: Px::new(video.height),
+            width: Px::new(width),
+            height: Px::new(height),
         };
         match self.scaling.get_tracking_invalidate(context) {
             ImageScaling::Aspect { mode, orientation } => {
@@ -106,28 +233,33 @@ impl VideoPlayer {
 
 impl Widget for VideoPlayer {
     fn redraw(&mut self, context: &mut GraphicsContext<'_, '_, '_, '_>) {
-        let mut inner = self.video.write();
-        let frame = inner.upload_frame.generation();
+        let inner = self.video.read();
         let _ = inner.upload_frame.get_tracking_redraw(context); // no data here, just to trigger redraw
 
-        let upload_frame = frame != self.last_frame;
-        self.last_frame = frame;
-
-        if upload_frame {
-            let last_frame_time = inner
-                .last_frame_time
-                .lock()
-                .map(|time| *time)
-                .unwrap_or_else(|_| Instant::now());
-            inner.set_av_offset(Instant::now() - last_frame_time);
-        }
+        // picks the frame due now, rather than the one decoded last
+        let upload_frame = inner.present_frame();
+        let orientation = inner
+            .orientation_override
+            .get_tracking_redraw(context)
+            .unwrap_or_else(|| inner.orientation.get_tracking_redraw(context));
 
         context.gfx.draw_with::<VideoRO>(VideoPrimitive::new(
             inner.id,
             Arc::clone(&inner.alive),
             Arc::clone(&inner.frame),
-            (inner.width as _, inner.height as _),
             upload_frame,
+            RenderParams {
+                colorimetry_override: inner.colorimetry_override.get_tracking_redraw(context),
+                tone_mapping: self.tone_mapping.get_tracking_redraw(context),
+                adjustments: PictureAdjustments {
+                    brightness: self.brightness.get_tracking_redraw(context),
+                    contrast: self.contrast.get_tracking_redraw(context),
+                    saturation: self.saturation.get_tracking_redraw(context),
+                    hue: self.hue.get_tracking_redraw(context),
+                    gamma: self.gamma.get_tracking_redraw(context),
+                },
+                orientation,
+            },
         ));
     }
 
@@ -0,0 +1,303 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+
+use crate::video::Frame;
+
+/// Number of decoded frames buffered ahead of presentation.
+const CAPACITY: usize = 3;
+
+/// How far ahead of their running time frames leave the sink, so that the queue fills up.
+pub(crate) fn lead(framerate: f64) -> gst::ClockTime {
+    gst::ClockTime::from_nseconds((CAPACITY as f64 * 1e9 / framerate) as u64)
+}
+
+/// Presentation statistics of a video, see [`Video::frame_stats`](crate::video::Video::frame_stats).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Frames that were shown.
+    pub presented: u64,
+    /// Frames that were shown more than a frame duration after they were due.
+    pub late: u64,
+    /// Frames that were decoded but never shown, as a newer frame was already due
+    /// when the widget was drawn, or the widget wasn't drawn at all.
+    pub dropped: u64,
+}
+
+/// Decoded frames waiting for presentation, ordered by their running time.
+///
+/// The decoder thread pushes frames ahead of time, and the widget takes the one
+/// due at the time it draws, so frames line up with the display instead of the decoder.
+/// Once the queue is full, the decoder thread waits for the widget to take a frame.
+#[derive(Debug, Default)]
+pub(crate) struct FrameQueue {
+    inner: Mutex<Queued>,
+    /// Notified when frames are taken out of the queue.
+    taken: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct Queued {
+    frames: VecDeque<Arc<Frame>>,
+    /// Frames dropped by [`FrameQueue::drop_stale`] since the last [`FrameQueue::pop_due`].
+    dropped: u64,
+    /// Number of frames at the front already reported by [`FrameQueue::announce_due`].
+    announced: usize,
+}
+
+impl Queued {
+    fn due(&self, now: Option<gst::ClockTime>) -> usize {
+        self.frames
+            .iter()
+            .take_while(|frame| is_due(frame, now))
+            .count()
+    }
+
+    fn take_front(&mut self, count: usize) {
+        self.frames.drain(..count);
+        self.announced = self.announced.saturating_sub(count);
+    }
+}
+
+impl FrameQueue {
+    fn inner(&self) -> MutexGuard<'_, Queued> {
+        self.inner.lock().expect("lock frame queue")
+    }
+
+    pub(crate) fn is_full(&self) -> bool {
+        self.inner().frames.len() >= CAPACITY
+    }
+
+    /// Adds a decoded frame, even if the queue is full.
+    pub(crate) fn push(&self, frame: Frame) {
+        let mut inner = self.inner();
+        // running time going backwards means the pipeline was flushed, e.g. by a seek
+        let flushed = inner.frames.back().is_some_and(|last| {
+            matches!((last.running_time, frame.running_time), (Some(last), Some(new)) if new < last)
+        });
+        if flushed {
+            inner.frames.clear();
+            inner.announced = 0;
+        }
+        inner.frames.push_back(Arc::new(frame));
+    }
+
+    /// Drops the queued frames, e.g. when pausing so the preroll frame is shown next.
+    pub(crate) fn clear(&self) {
+        let mut inner = self.inner();
+        inner.frames.clear();
+        inner.announced = 0;
+        self.taken.notify_all();
+    }
+
+    /// Checks if frames became due at `now`, the running time of the pipeline, since the
+    /// last call. When `now` is `None`, e.g. while paused, any frame is due.
+    pub(crate) fn announce_due(&self, now: Option<gst::ClockTime>) -> bool {
+        let mut inner = self.inner();
+        let due = inner.due(now);
+        let new = due > inner.announced;
+        inner.announced = inner.announced.max(due);
+        new
+    }
+
+    /// Get the time from `now` until the next frame is due, `None` if none is waiting.
+    pub(crate) fn next_due_in(&self, now: Option<gst::ClockTime>) -> Option<gst::ClockTime> {
+        let inner = self.inner();
+        let next = inner.frames.get(inner.due(now))?;
+        Some(next.running_time?.saturating_sub(now?))
+    }
+
+    /// Drops the frames due at `now` but the newest, counting them as dropped,
+    /// so decoding goes on while the widget isn't drawn.
+    pub(crate) fn drop_stale(&self, now: Option<gst::ClockTime>) {
+        let mut inner = self.inner();
+        let stale = inner.due(now).saturating_sub(1);
+        inner.take_front(stale);
+        inner.dropped += stale as u64;
+    }
+
+    /// Blocks until a frame is taken or `timeout` passed.
+    pub(crate) fn wait_taken(&self, timeout: Duration) {
+        let inner = self.inner();
+        let _ = self.taken.wait_timeout(inner, timeout);
+    }
+
+    /// Takes the newest frame due at `now`, dropping the older ones.
+    /// Frames dropped by [`FrameQueue::drop_stale`] are counted as well.
+    pub(crate) fn pop_due(
+        &self,
+        now: Option<gst::ClockTime>,
+        frame_duration: Duration,
+        stats: &mut FrameStats,
+    ) -> Option<Arc<Frame>> {
+        let mut inner = self.inner();
+        stats.dropped += std::mem::take(&mut inner.dropped);
+        let due = inner.due(now);
+        if due == 0 {
+            return None;
+        }
+        stats.dropped += due as u64 - 1;
+        inner.take_front(due - 1);
+        let frame = inner.frames.pop_front()?;
+        inner.announced = inner.announced.saturating_sub(1);
+        self.taken.notify_all();
+
+        stats.presented += 1;
+        if let (Some(now), Some(running_time)) = (now, frame.running_time) {
+            if Duration::from(now.saturating_sub(running_time)) > frame_duration {
+                stats.late += 1;
+            }
+        }
+        Some(frame)
+    }
+}
+
+fn is_due(frame: &Frame, now: Option<gst::ClockTime>) -> bool {
+    match (now, frame.running_time) {
+        (Some(now), Some(running_time)) => running_time <= now,
+        _ => true,
+    }
+}
+
+/// Get the current running time of `pipeline`, `None` unless it's playing.
+pub(crate) fn running_time(pipeline: &gst::Pipeline) -> Option<gst::ClockTime> {
+    if pipeline.current_state() != gst::State::Playing {
+        return None;
+    }
+    let clock = pipeline.clock()?;
+    let base_time = pipeline.base_time()?;
+    clock.time()?.checked_sub(base_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gstreamer_video as gst_video;
+
+    fn frame(running_time: u64) -> Frame {
+        let info = gst_video::VideoInfo::builder(gst_video::VideoFormat::Rgba, 2, 2)
+            .build()
+            .unwrap();
+        let buffer = gst::Buffer::with_size(info.size()).unwrap();
+        Frame::new(
+            buffer,
+            &info,
+            None,
+            Some(gst::ClockTime::from_mseconds(running_time)),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn pop_due() {
+        gst::init().unwrap();
+        let stats = |presented, late, dropped| FrameStats {
+            presented,
+            late,
+            dropped,
+        };
+        // running times pushed, now, running time of the presented frame, stats afterwards
+        let cases: &[(&[u64], Option<u64>, Option<u64>, FrameStats)] = &[
+            (&[0, 40, 80], Some(50), Some(40), stats(1, 0, 1)),
+            (&[0, 40, 80], Some(0), Some(0), stats(1, 0, 0)),
+            (&[100], Some(50), None, stats(0, 0, 0)),
+            (&[0], Some(100), Some(0), stats(1, 1, 0)),
+            // a seek rewinds the running time, the frames before it are flushed
+            (&[80, 120, 0], Some(10), Some(0), stats(1, 0, 0)),
+            // frames pushed past the capacity are kept
+            (&[0, 40, 80, 120], Some(130), Some(120), stats(1, 0, 3)),
+            // while paused
+            (&[0, 40, 80], None, Some(80), stats(1, 0, 2)),
+        ];
+
+        for (pushed, now, expected, expected_stats) in cases {
+            let queue = FrameQueue::default();
+            for &running_time in *pushed {
+                queue.push(frame(running_time));
+            }
+            let mut frame_stats = FrameStats::default();
+            let presented = queue.pop_due(
+                now.map(gst::ClockTime::from_mseconds),
+                Duration::from_millis(40),
+                &mut frame_stats,
+            );
+            assert_eq!(
+                presented.and_then(|frame| frame.running_time),
+                expected.map(gst::ClockTime::from_mseconds),
+                "{pushed:?} at {now:?}"
+            );
+            assert_eq!(frame_stats, *expected_stats, "{pushed:?} at {now:?}");
+        }
+    }
+
+    #[test]
+    fn announce_due() {
+        gst::init().unwrap();
+        let ms = |ms| Some(gst::ClockTime::from_mseconds(ms));
+        let queue = FrameQueue::default();
+        for running_time in [0, 40, 80] {
+            queue.push(frame(running_time));
+        }
+        assert!(queue.is_full());
+        assert!(queue.announce_due(ms(10)));
+        assert!(!queue.announce_due(ms(20)));
+        assert_eq!(queue.next_due_in(ms(20)), ms(20));
+        assert!(queue.announce_due(ms(50)));
+
+        queue.pop_due(
+            ms(50),
+            Duration::from_millis(40),
+            &mut FrameStats::default(),
+        );
+        assert!(!queue.is_full());
+        assert!(!queue.announce_due(ms(60)));
+        assert!(queue.announce_due(ms(90)));
+        assert_eq!(queue.next_due_in(ms(90)), None);
+    }
+
+    #[test]
+    fn drop_stale() {
+        gst::init().unwrap();
+        let ms = |ms| Some(gst::ClockTime::from_mseconds(ms));
+        let queue = FrameQueue::default();
+        for running_time in [0, 40, 80] {
+            queue.push(frame(running_time));
+        }
+        // the widget isn't drawn, only the newest due frame is kept
+        queue.drop_stale(ms(50));
+        assert!(!queue.is_full());
+
+        let mut frame_stats = FrameStats::default();
+        let presented = queue.pop_due(ms(50), Duration::from_millis(40), &mut frame_stats);
+        assert_eq!(presented.and_then(|frame| frame.running_time), ms(40));
+        assert_eq!(frame_stats.dropped, 1);
+    }
+
+    #[test]
+    fn clear() {
+        gst::init().unwrap();
+        let queue = FrameQueue::default();
+        queue.push(frame(0));
+        queue.push(frame(40));
+        queue.clear();
+        assert!(!queue.announce_due(None));
+
+        // frames dropped before clearing still count
+        for running_time in [0, 40, 80] {
+            queue.push(frame(running_time));
+        }
+        queue.drop_stale(None);
+        queue.clear();
+        let mut frame_stats = FrameStats::default();
+        assert!(queue
+            .pop_due(None, Duration::from_millis(40), &mut frame_stats)
+            .is_none());
+        assert_eq!(frame_stats.dropped, 2);
+    }
+}
@@ -5,8 +5,31 @@ struct VertexOutput {
 
 struct Uniforms {
     rect: vec4<f32>,
+    // YUV to RGB conversion of the stream colorimetry, including its range
+    yuv_matrix: mat3x3<f32>,
+    yuv_offset: vec4<f32>,
+    // converts linear RGB from the stream primaries to BT.709
+    gamut_matrix: mat3x3<f32>,
+    // 0 = SDR, 1 = PQ, 2 = HLG
+    transfer: u32,
+    // 0 = clip, 1 = Reinhard, 2 = Hable, 3 = BT.2390
+    tone_mapping: u32,
+    // peak luminance of the content, relative to SDR white
+    peak: f32,
+    // picture adjustments, see `adjust`
+    gamma: f32,
+    brightness: f32,
+    contrast: f32,
+    saturation: f32,
+    // in radians
+    hue: f32,
+    // maps the screen to the texture, both relative to the center, to rotate and flip the video
+    orientation: mat2x2<f32>,
 }
 
+// luminance of SDR white in cd/m²
+const SDR_WHITE: f32 = 203.0;
+
 @group(0) @binding(0)
 var tex_y: texture_2d<f32>;
 
@@ -19,6 +42,9 @@ var s: sampler;
 @group(0) @binding(3)
 var<uniform> uniforms: Uniforms;
 
+@group(0) @binding(4)
+var tex_v: texture_2d<f32>;
+
 @vertex
 fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> VertexOutput {
     let quad = array<vec2<f32>, 6>(
@@ -35,29 +61,156 @@ fn vs_main(@builtin(vertex_index) in_vertex_index: u32) -> VertexOutput {
     out.uv.x = select(0.0, 2.0, in_vertex_index == 1u);
     out.uv.y = select(0.0, 2.0, in_vertex_index == 2u);
     out.position = vec4<f32>(out.uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 1.0, 1.0);
+    out.uv = uniforms.orientation * (out.uv - vec2<f32>(0.5)) + vec2<f32>(0.5);
     return out;
 }
 
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let yuv2r = vec3<f32>(1.164, 0.0, 1.596);
-    let yuv2g = vec3<f32>(1.164, -0.391, -0.813);
-    let yuv2b = vec3<f32>(1.164, 2.018, 0.0);
-
-    var yuv = vec3<f32>(0.0);
-    yuv.x = textureSample(tex_y, s, in.uv).r - 0.0625;
-    yuv.y = textureSample(tex_uv, s, in.uv).r - 0.5;
-    yuv.z = textureSample(tex_uv, s, in.uv).g - 0.5;
+fn yuv_to_rgb(yuv: vec3<f32>) -> vec3<f32> {
+    let rgb = uniforms.yuv_matrix * (yuv - uniforms.yuv_offset.xyz);
+    return clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0));
+}
 
-    var rgb = vec3<f32>(0.0);
-    rgb.x = dot(yuv, yuv2r);
-    rgb.y = dot(yuv, yuv2g);
-    rgb.z = dot(yuv, yuv2b);
+// brightness, contrast, saturation, hue and gamma, applied to the non-linear RGB like a TV does
+fn adjust(rgb: vec3<f32>) -> vec3<f32> {
+    var c = (rgb - vec3<f32>(0.5)) * uniforms.contrast + vec3<f32>(0.5 + uniforms.brightness);
+    let luma = dot(c, vec3<f32>(0.2126, 0.7152, 0.0722));
+    c = mix(vec3<f32>(luma), c, uniforms.saturation);
+    // rotates around the grey axis
+    let k = vec3<f32>(0.57735027);
+    let cos_h = cos(uniforms.hue);
+    let sin_h = sin(uniforms.hue);
+    c = c * cos_h + cross(k, c) * sin_h + k * dot(k, c) * (1.0 - cos_h);
+    return pow(clamp(c, vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(1.0 / uniforms.gamma));
+}
 
+fn srgb_to_linear(rgb: vec3<f32>) -> vec3<f32> {
     let threshold = rgb <= vec3<f32>(0.04045);
     let hi = pow((rgb + vec3<f32>(0.055)) / vec3<f32>(1.055), vec3<f32>(2.4));
     let lo = rgb * vec3<f32>(1.0 / 12.92);
-    rgb = select(hi, lo, threshold);
+    return select(hi, lo, threshold);
+}
+
+// SMPTE ST 2084 EOTF, returns luminance in units of 10000 cd/m²
+fn pq_eotf(e: f32) -> f32 {
+    let p = pow(max(e, 0.0), 1.0 / 78.84375);
+    return pow(max(p - 0.8359375, 0.0) / (18.8515625 - 18.6875 * p), 1.0 / 0.1593017578125);
+}
+
+fn pq_inverse_eotf(y: f32) -> f32 {
+    let p = pow(max(y, 0.0), 0.1593017578125);
+    return pow((0.8359375 + 18.8515625 * p) / (1.0 + 18.6875 * p), 78.84375);
+}
+
+fn pq_to_linear(rgb: vec3<f32>) -> vec3<f32> {
+    let nits = vec3<f32>(pq_eotf(rgb.r), pq_eotf(rgb.g), pq_eotf(rgb.b)) * 10000.0;
+    return nits / SDR_WHITE;
+}
+
+fn hlg_inverse_oetf(e: f32) -> f32 {
+    if e <= 0.5 {
+        return e * e / 3.0;
+    }
+    return (exp((e - 0.55991073) / 0.17883277) + 0.28466892) / 12.0;
+}
+
+fn hlg_to_linear(rgb: vec3<f32>) -> vec3<f32> {
+    let scene = vec3<f32>(hlg_inverse_oetf(rgb.r), hlg_inverse_oetf(rgb.g), hlg_inverse_oetf(rgb.b));
+    // OOTF with the system gamma of a 1000 cd/m² display, scaled to the content peak
+    let luma = dot(scene, vec3<f32>(0.2627, 0.6780, 0.0593));
+    return uniforms.peak * pow(max(luma, 1e-6), 0.2) * scene;
+}
+
+fn hable(x: f32) -> f32 {
+    let a = 0.15;
+    let b = 0.50;
+    let c = 0.10;
+    let d = 0.20;
+    let e = 0.02;
+    let f = 0.30;
+    return (x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f) - e / f;
+}
 
-    return vec4<f32>(rgb, 1.0);
+// ITU-R BT.2390 EETF, mapping [0, peak] to [0, 1] in the PQ domain
+fn bt2390(x: f32, peak: f32) -> f32 {
+    let source_peak = pq_inverse_eotf(peak * SDR_WHITE / 10000.0);
+    let e1 = pq_inverse_eotf(x * SDR_WHITE / 10000.0) / source_peak;
+    let max_lum = pq_inverse_eotf(SDR_WHITE / 10000.0) / source_peak;
+    let ks = 1.5 * max_lum - 0.5;
+
+    var e2 = e1;
+    if e1 > ks {
+        let t = (e1 - ks) / (1.0 - ks);
+        let t2 = t * t;
+        let t3 = t2 * t;
+        e2 = (2.0 * t3 - 3.0 * t2 + 1.0) * ks + (t3 - 2.0 * t2 + t) * (1.0 - ks) + (-2.0 * t3 + 3.0 * t2) * max_lum;
+    }
+    return pq_eotf(e2 * source_peak) * 10000.0 / SDR_WHITE;
+}
+
+// maps the brightest channel, keeping the hue
+fn tone_map(rgb: vec3<f32>) -> vec3<f32> {
+    let peak = uniforms.peak;
+    let m = max(rgb.r, max(rgb.g, rgb.b));
+    if m <= 0.0 || peak <= 1.0 {
+        return rgb;
+    }
+
+    var mapped = m;
+    switch uniforms.tone_mapping {
+        case 1u: {
+            mapped = m * (1.0 + m / (peak * peak)) / (1.0 + m);
+        }
+        case 2u: {
+            mapped = hable(m) / hable(peak);
+        }
+        case 3u: {
+            mapped = bt2390(m, peak);
+        }
+        default: {}
+    }
+    return rgb * (mapped / m);
+}
+
+// converts non-linear RGB of the stream to linear BT.709 in the SDR range
+fn to_display(rgb: vec3<f32>) -> vec3<f32> {
+    switch uniforms.transfer {
+        case 1u: {
+            return clamp(tone_map(uniforms.gamut_matrix * pq_to_linear(rgb)), vec3<f32>(0.0), vec3<f32>(1.0));
+        }
+        case 2u: {
+            return clamp(tone_map(uniforms.gamut_matrix * hlg_to_linear(rgb)), vec3<f32>(0.0), vec3<f32>(1.0));
+        }
+        default: {
+            return uniforms.gamut_matrix * srgb_to_linear(rgb);
+        }
+    }
+}
+
+// NV12, P010 and P016: a Y plane and an interleaved UV plane
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    var yuv = vec3<f32>(0.0);
+    yuv.x = textureSample(tex_y, s, in.uv).r;
+    yuv.y = textureSample(tex_uv, s, in.uv).r;
+    yuv.z = textureSample(tex_uv, s, in.uv).g;
+
+    return vec4<f32>(to_display(adjust(yuv_to_rgb(yuv))), 1.0);
+}
+
+// I420: separate Y, U and V planes
+@fragment
+fn fs_planar(in: VertexOutput) -> @location(0) vec4<f32> {
+    var yuv = vec3<f32>(0.0);
+    yuv.x = textureSample(tex_y, s, in.uv).r;
+    yuv.y = textureSample(tex_uv, s, in.uv).r;
+    yuv.z = textureSample(tex_v, s, in.uv).r;
+
+    return vec4<f32>(to_display(adjust(yuv_to_rgb(yuv))), 1.0);
+}
+
+// RGBA and BGRA: the texture format takes care of the channel order
+@fragment
+fn fs_packed(in: VertexOutput) -> @location(0) vec4<f32> {
+    let rgb = textureSample(tex_y, s, in.uv).rgb;
+    return vec4<f32>(to_display(adjust(rgb)), 1.0);
 }
@@ -0,0 +1,493 @@
+use cushy::{
+    figures::units::Lp,
+    kludgine::cosmic_text::{Style, Weight},
+    styles::{
+        components::{FontStyle, FontWeight, TextColor, TextSize},
+        Color, Dimension,
+    },
+    widget::{MakeWidget, WidgetInstance, WidgetList},
+    widgets::wrap::{Wrap, WrapAlign},
+};
+
+/// Size step of the `big` and `small` tags and the named sizes, as used by Pango.
+const SIZE_STEP: f32 = 1.2;
+
+/// A subtitle shown on screen, see [`VideoPlayer::get_subtitles`](crate::player::VideoPlayer::get_subtitles).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SubtitleCue {
+    /// The text in reading order, lines are separated by `\n` within the spans.
+    pub spans: Vec<SubtitleSpan>,
+}
+
+/// A run of subtitle text sharing one style.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubtitleSpan {
+    pub text: String,
+    pub style: SubtitleStyle,
+}
+
+/// Formatting of a [`SubtitleSpan`], parsed from the Pango markup of the subtitle stream.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SubtitleStyle {
+    pub italic: bool,
+    pub bold: bool,
+    pub underline: bool,
+    /// `None` for the default text color.
+    pub color: Option<Color>,
+    /// `None` for the default text size.
+    pub size: Option<FontSize>,
+}
+
+/// Size of subtitle text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FontSize {
+    /// An absolute size in points.
+    Points(f32),
+    /// A factor of the default text size, e.g. `1.2` for `<big>`.
+    Scale(f32),
+}
+
+impl FontSize {
+    fn scaled(size: Option<FontSize>, factor: f32) -> FontSize {
+        match size {
+            Some(FontSize::Points(points)) => FontSize::Points(points * factor),
+            Some(FontSize::Scale(scale)) => FontSize::Scale(scale * factor),
+            None => FontSize::Scale(factor),
+        }
+    }
+
+    /// Parses the `size` attribute of a `span` relative to the size of the parent.
+    fn parse(value: &str, parent: Option<FontSize>) -> Option<FontSize> {
+        let named = |steps: i32| Some(FontSize::Scale(SIZE_STEP.powi(steps)));
+        match value {
+            "xx-small" => named(-3),
+            "x-small" => named(-2),
+            "small" => named(-1),
+            "medium" => named(0),
+            "large" => named(1),
+            "x-large" => named(2),
+            "xx-large" => named(3),
+            "smaller" => Some(FontSize::scaled(parent, 1.0 / SIZE_STEP)),
+            "larger" => Some(FontSize::scaled(parent, SIZE_STEP)),
+            _ => {
+                if let Some(percent) = value.strip_suffix('%') {
+                    let factor = percent.trim().parse::<f32>().ok()? / 100.0;
+                    Some(FontSize::scaled(parent, factor))
+                } else if let Some(points) = value.strip_suffix("pt") {
+                    points.trim().parse().ok().map(FontSize::Points)
+                } else {
+                    // in 1024ths of a point
+                    let size = value.parse::<f32>().ok()?;
+                    Some(FontSize::Points(size / 1024.0))
+                }
+            }
+        }
+    }
+}
+
+impl SubtitleCue {
+    /// Creates a cue of unformatted text.
+    pub(crate) fn plain(text: &str) -> Self {
+        let mut cue = SubtitleCue::default();
+        cue.push(text, SubtitleStyle::default());
+        cue
+    }
+
+    /// Parses Pango markup, as produced by `subparse` for SRT, SSA and most other formats.
+    /// Unknown tags are ignored, keeping their text.
+    pub(crate) fn from_markup(markup: &str) -> Self {
+        let mut cue = SubtitleCue::default();
+        let mut styles = vec![SubtitleStyle::default()];
+        let mut rest = markup;
+
+        while let Some(start) = rest.find('<') {
+            let Some(len) = rest[start..].find('>') else {
+                break;
+            };
+            cue.push(&decode(&rest[..start]), *styles.last().unwrap());
+            let tag = &rest[start + 1..start + len];
+            rest = &rest[start + len + 1..];
+
+            if tag.starts_with('/') {
+                // the outermost style is never popped, so stray closing tags are harmless
+                if styles.len() > 1 {
+                    styles.pop();
+                }
+            } else if !tag.ends_with('/') {
+                let style = SubtitleStyle::parse_tag(tag, *styles.last().unwrap());
+                styles.push(style);
+            }
+        }
+        cue.push(&decode(rest), *styles.last().unwrap());
+        cue
+    }
+
+    fn push(&mut self, text: &str, style: SubtitleStyle) {
+        if text.is_empty() {
+            return;
+        }
+        match self.spans.last_mut() {
+            Some(last) if last.style == style => last.text.push_str(text),
+            _ => self.spans.push(SubtitleSpan {
+                text: text.to_string(),
+                style,
+            }),
+        }
+    }
+
+    /// The text without formatting.
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    /// Creates a widget showing the cue with its formatting, each line centered.
+    /// [`FontSize::Scale`] is relative to `text_size`.
+    ///
+    /// Underlines are not shown, as cushy can't draw them.
+    pub fn to_widget(&self, text_size: Lp) -> WidgetInstance {
+        let mut lines = vec![WidgetList::new()];
+        for span in &self.spans {
+            for (index, line) in span.text.split('\n').enumerate() {
+                if index > 0 {
+                    lines.push(WidgetList::new());
+                }
+                if !line.is_empty() {
+                    let label = span.style.apply(line.to_string(), text_size);
+                    lines.last_mut().unwrap().push(label);
+                }
+            }
+        }
+
+        lines
+            .into_iter()
+            .map(|line| Wrap::new(line).align(WrapAlign::Center))
+            .collect::<WidgetList>()
+            .into_rows()
+            .make_widget()
+    }
+}
+
+impl SubtitleStyle {
+    /// Applies an opening tag, e.g. `span foreground="red"`, to the style of its parent.
+    fn parse_tag(tag: &str, parent: SubtitleStyle) -> SubtitleStyle {
+        let (name, attributes) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        let mut style = parent;
+        match name.to_ascii_lowercase().as_str() {
+            "b" => style.bold = true,
+            "i" => style.italic = true,
+            "u" => style.underline = true,
+            "big" => style.size = Some(FontSize::scaled(parent.size, SIZE_STEP)),
+            "small" => style.size = Some(FontSize::scaled(parent.size, 1.0 / SIZE_STEP)),
+            "span" => {
+                for (attribute, value) in parse_attributes(attributes) {
+                    style.apply_attribute(&attribute, &value, parent);
+                }
+            }
+            // left over from SRT files using HTML, its sizes don't match Pango's
+            "font" => {
+                for (attribute, value) in parse_attributes(attributes) {
+                    if attribute == "color" {
+                        style.apply_attribute(&attribute, &value, parent);
+                    }
+                }
+            }
+            _ => {}
+        }
+        style
+    }
+
+    fn apply_attribute(&mut self, attribute: &str, value: &str, parent: SubtitleStyle) {
+        match attribute {
+            "foreground" | "fgcolor" | "color" => {
+                if let Some(color) = parse_color(value) {
+                    self.color = Some(color);
+                }
+            }
+            "style" | "font_style" => self.italic = matches!(value, "italic" | "oblique"),
+            "weight" | "font_weight" => {
+                self.bold = match value {
+                    "bold" | "ultrabold" | "heavy" | "semibold" | "ultraheavy" => true,
+                    _ => value.parse::<u32>().is_ok_and(|weight| weight >= 600),
+                };
+            }
+            "underline" => self.underline = value != "none",
+            "size" | "font_size" => {
+                if let Some(size) = FontSize::parse(value, parent.size) {
+                    self.size = Some(size);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn apply(&self, text: String, text_size: Lp) -> WidgetInstance {
+        let size = match self.size {
+            Some(FontSize::Points(points)) => Lp::points_f(points),
+            Some(FontSize::Scale(scale)) => text_size * scale,
+            None => text_size,
+        };
+        let style = if self.italic {
+            Style::Italic
+        } else {
+            Style::Normal
+        };
+        let weight = if self.bold {
+            Weight::BOLD
+        } else {
+            Weight::NORMAL
+        };
+
+        let label = text
+            .with(&TextSize, Dimension::Lp(size))
+            .with(&FontStyle, style)
+            .with(&FontWeight, weight);
+        match self.color {
+            Some(color) => label.with(&TextColor, color).make_widget(),
+            None => label.make_widget(),
+        }
+    }
+}
+
+/// Decodes the entities of markup text, e.g. `&amp;`.
+fn decode(text: &str) -> String {
+    html_escape::decode_html_entities(text).to_string()
+}
+
+/// Splits `name="value"` pairs, accepting single, double or no quotes.
+fn parse_attributes(mut attributes: &str) -> Vec<(String, String)> {
+    let mut parsed = Vec::new();
+    loop {
+        attributes = attributes.trim_start();
+        let Some((name, rest)) = attributes.split_once('=') else {
+            break;
+        };
+        let rest = rest.trim_start();
+        let (value, rest) = match rest.chars().next() {
+            Some(quote @ ('"' | '\'')) => rest[1..].split_once(quote).unwrap_or((&rest[1..], "")),
+            _ => rest.split_once(char::is_whitespace).unwrap_or((rest, "")),
+        };
+        parsed.push((name.trim().to_ascii_lowercase(), decode(value)));
+        attributes = rest;
+    }
+    parsed
+}
+
+/// Parses `#rgb`, `#rrggbb`, `#rrggbbaa` or one of the common color names.
+fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim().to_ascii_lowercase();
+    if let Some(hex) = value.strip_prefix('#') {
+        let channel = |index: usize, len: usize| {
+            let digits = hex.get(index * len..(index + 1) * len)?;
+            let channel = u8::from_str_radix(digits, 16).ok()?;
+            // expands `#f80` to `#ff8800`
+            Some(if len == 1 { channel * 17 } else { channel })
+        };
+        return match hex.len() {
+            3 => Some(Color::new(
+                channel(0, 1)?,
+                channel(1, 1)?,
+                channel(2, 1)?,
+                255,
+            )),
+            6 => Some(Color::new(
+                channel(0, 2)?,
+                channel(1, 2)?,
+                channel(2, 2)?,
+                255,
+            )),
+            8 => Some(Color::new(
+                channel(0, 2)?,
+                channel(1, 2)?,
+                channel(2, 2)?,
+                channel(3, 2)?,
+            )),
+            _ => None,
+        };
+    }
+
+    let (r, g, b) = match value.as_str() {
+        "white" => (255, 255, 255),
+        "black" => (0, 0, 0),
+        "red" => (255, 0, 0),
+        "green" => (0, 128, 0),
+        "lime" => (0, 255, 0),
+        "blue" => (0, 0, 255),
+        "yellow" => (255, 255, 0),
+        "cyan" | "aqua" => (0, 255, 255),
+        "magenta" | "fuchsia" => (255, 0, 255),
+        "orange" => (255, 165, 0),
+        "purple" => (128, 0, 128),
+        "gray" | "grey" => (128, 128, 128),
+        "silver" => (192, 192, 192),
+        _ => {
+            log::warn!("unknown subtitle color {value}");
+            return None;
+        }
+    };
+    Some(Color::new(r, g, b, 255))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ITALIC: SubtitleStyle = SubtitleStyle {
+        italic: true,
+        bold: false,
+        underline: false,
+        color: None,
+        size: None,
+    };
+    const BOLD: SubtitleStyle = SubtitleStyle {
+        italic: false,
+        bold: true,
+        underline: false,
+        color: None,
+        size: None,
+    };
+    const PLAIN: SubtitleStyle = SubtitleStyle {
+        italic: false,
+        bold: false,
+        underline: false,
+        color: None,
+        size: None,
+    };
+
+    fn spans(spans: &[(&str, SubtitleStyle)]) -> Vec<SubtitleSpan> {
+        spans
+            .iter()
+            .map(|(text, style)| SubtitleSpan {
+                text: text.to_string(),
+                style: *style,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn from_markup() {
+        let bold_italic = SubtitleStyle {
+            bold: true,
+            ..ITALIC
+        };
+        let red = SubtitleStyle {
+            color: Some(Color::new(255, 0, 0, 255)),
+            ..PLAIN
+        };
+        let cases: &[(&str, &[(&str, SubtitleStyle)])] = &[
+            ("", &[]),
+            ("plain text", &[("plain text", PLAIN)]),
+            ("two\nlines", &[("two\nlines", PLAIN)]),
+            (
+                "<i>a <b>b</b></i> c",
+                &[("a ", ITALIC), ("b", bold_italic), (" c", PLAIN)],
+            ),
+            // stray closing tags don't end the outermost style
+            ("</i>a</b> b", &[("a b", PLAIN)]),
+            ("<i>a</i></i>b", &[("a", ITALIC), ("b", PLAIN)]),
+            // unknown and self-closing tags keep their text
+            ("<x>a</x><br/>b", &[("ab", PLAIN)]),
+            // spans with the same style are merged
+            ("<b>a</b><b>b</b>", &[("ab", BOLD)]),
+            (
+                "a &amp; &lt;b&gt; &quot;c&quot;",
+                &[("a & <b> \"c\"", PLAIN)],
+            ),
+            ("a < b", &[("a < b", PLAIN)]),
+            ("<B>a</B>", &[("a", BOLD)]),
+            ("<span weight=\"700\">a</span>", &[("a", BOLD)]),
+            ("<span weight='light'>a</span>", &[("a", PLAIN)]),
+            ("<span font_style=italic>a</span>", &[("a", ITALIC)]),
+            ("<span foreground=\"red\">a</span>", &[("a", red)]),
+            ("<font color=\"#FF0000\" size=\"7\">a</font>", &[("a", red)]),
+        ];
+
+        for (markup, expected) in cases {
+            assert_eq!(
+                SubtitleCue::from_markup(markup).spans,
+                spans(expected),
+                "{markup:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn font_size() {
+        let scale = |steps: i32| Some(FontSize::Scale(SIZE_STEP.powi(steps)));
+        let cases = [
+            ("<big>a</big>", scale(1)),
+            ("<small>a</small>", scale(-1)),
+            ("<big><big>a</big></big>", scale(2)),
+            ("<span size=\"x-large\">a</span>", scale(2)),
+            ("<span size=\"medium\">a</span>", scale(0)),
+            ("<big><span size=\"smaller\">a</span></big>", scale(0)),
+            ("<span size=\"150%\">a</span>", Some(FontSize::Scale(1.5))),
+            ("<span size=\"12pt\">a</span>", Some(FontSize::Points(12.0))),
+            (
+                "<span size=\"10240\">a</span>",
+                Some(FontSize::Points(10.0)),
+            ),
+            (
+                "<span size=\"12pt\"><big>a</big></span>",
+                Some(FontSize::Points(12.0 * SIZE_STEP)),
+            ),
+            ("<span size=\"huge\">a</span>", None),
+        ];
+
+        let approx = |a: Option<FontSize>, b: Option<FontSize>| match (a, b) {
+            (Some(FontSize::Scale(a)), Some(FontSize::Scale(b)))
+            | (Some(FontSize::Points(a)), Some(FontSize::Points(b))) => (a - b).abs() < 1e-4,
+            (None, None) => true,
+            _ => false,
+        };
+        for (markup, expected) in cases {
+            let cue = SubtitleCue::from_markup(markup);
+            let size = cue.spans[0].style.size;
+            assert!(approx(size, expected), "{markup:?}: {size:?}");
+        }
+    }
+
+    #[test]
+    fn colors() {
+        let cases = [
+            ("#f80", Some(Color::new(255, 136, 0, 255))),
+            ("#FF8800", Some(Color::new(255, 136, 0, 255))),
+            ("#11223344", Some(Color::new(17, 34, 51, 68))),
+            (" White ", Some(Color::new(255, 255, 255, 255))),
+            ("aqua", Some(Color::new(0, 255, 255, 255))),
+            ("#12", None),
+            ("#ggg", None),
+            ("chartreuse", None),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(parse_color(value), expected, "{value:?}");
+        }
+    }
+
+    #[test]
+    fn attributes() {
+        let pairs = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<Vec<_>>()
+        };
+        let cases = [
+            ("", pairs(&[])),
+            ("color=red", pairs(&[("color", "red")])),
+            (
+                "Foreground=\"#fff\" size='large' weight = bold",
+                pairs(&[
+                    ("foreground", "#fff"),
+                    ("size", "large"),
+                    ("weight", "bold"),
+                ]),
+            ),
+            ("font=\"a &amp; b\"", pairs(&[("font", "a & b")])),
+            ("color=\"unterminated", pairs(&[("color", "unterminated")])),
+        ];
+        for (attributes, expected) in cases {
+            assert_eq!(parse_attributes(attributes), expected, "{attributes:?}");
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_tag as gst_tag;
+
+use crate::metadata;
+use crate::Error;
+
+/// Name of the application message posted when `playbin` finds a new set of streams.
+pub(crate) const TRACKS_CHANGED: &str = "cushy-video/tracks-changed";
+
+/// The kind of a media track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TrackKind {
+    Video,
+    Audio,
+    Subtitle,
+}
+
+impl TrackKind {
+    /// Name of the `playbin` property/signal infix for this kind.
+    fn playbin_name(self) -> &'static str {
+        match self {
+            TrackKind::Video => "video",
+            TrackKind::Audio => "audio",
+            TrackKind::Subtitle => "text",
+        }
+    }
+}
+
+/// A single audio, video or subtitle stream of the media.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Track {
+    pub kind: TrackKind,
+    /// Index of the track within its kind, used by [`Video::select_track`](crate::video::Video::select_track).
+    pub index: usize,
+    /// ISO 639 language code, e.g. `en` or `deu`.
+    pub language: Option<String>,
+    pub codec: Option<String>,
+    pub title: Option<String>,
+    /// Exact or average bitrate in bits per second, or the nominal bitrate if unknown.
+    pub bitrate: Option<u32>,
+    /// Number of audio channels, only for audio tracks.
+    pub channels: Option<i32>,
+    /// Bitmask of the audio channel positions, only for audio tracks.
+    pub channel_mask: Option<u64>,
+}
+
+/// Preferred languages to select when the tracks of the media become known.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackPreferences {
+    pub audio: Option<String>,
+    pub subtitle: Option<String>,
+}
+
+/// Posts a message to the bus whenever `playbin` changes its streams, as the signals are
+/// emitted from streaming threads where tracks can't be switched.
+pub(crate) fn watch_tracks(pipeline: &gst::Pipeline) {
+    if !pipeline.has_property("n-audio", None) {
+        return;
+    }
+    for signal in ["video-changed", "audio-changed", "text-changed"] {
+        pipeline.connect(signal, false, |values| {
+            let element = values[0].get::<gst::Element>().ok()?;
+            let _ = element.post_message(gst::message::Application::new(
+                gst::Structure::new_empty(TRACKS_CHANGED),
+            ));
+            None
+        });
+    }
+}
+
+pub(crate) fn tracks(pipeline: &gst::Pipeline, kind: TrackKind) -> Vec<Track> {
+    let name = kind.playbin_name();
+    if !pipeline.has_property(&format!("n-{name}"), None) {
+        return Vec::new();
+    }
+
+    let count = pipeline.property::<i32>(&format!("n-{name}"));
+    (0..count)
+        .map(|i| {
+            let tags =
+                pipeline.emit_by_name::<Option<gst::TagList>>(&format!("get-{name}-tags"), &[&i]);
+
+            let caps = (kind == TrackKind::Audio)
+                .then(|| pipeline.emit_by_name::<Option<gst::Pad>>("get-audio-pad", &[&i]))
+                .flatten()
+                .and_then(|pad| pad.current_caps());
+            let structure = caps.as_ref().and_then(|caps| caps.structure(0));
+
+            Track {
+                kind,
+                index: i as usize,
+                language: tags.as_ref().and_then(|tags| {
+                    tags.get::<gst::tags::LanguageCode>()
+                        .map(|v| v.get().to_string())
+                }),
+                codec: tags.as_ref().and_then(|tags| {
+                    match kind {
+                        TrackKind::Video => tags.get::<gst::tags::VideoCodec>(),
+                        TrackKind::Audio => tags.get::<gst::tags::AudioCodec>(),
+                        TrackKind::Subtitle => tags.get::<gst::tags::SubtitleCodec>(),
+                    }
+                    .or_else(|| tags.get::<gst::tags::Codec>())
+                    .map(|v| v.get().to_string())
+                }),
+                title: tags
+                    .as_ref()
+                    .and_then(|tags| tags.get::<gst::tags::Title>().map(|v| v.get().to_string())),
+                bitrate: tags.as_ref().and_then(metadata::bitrate),
+                channels: structure.and_then(|s| s.get::<i32>("channels").ok()),
+                channel_mask: structure
+                    .and_then(|s| s.get::<gst::Bitmask>("channel-mask").ok().map(|mask| *mask)),
+            }
+        })
+        .collect()
+}
+
+/// Get the tags of the active track of the given kind.
+pub(crate) fn current_tags(pipeline: &gst::Pipeline, kind: TrackKind) -> Option<gst::TagList> {
+    let index = i32::try_from(current_track(pipeline, kind)?).ok()?;
+    pipeline.emit_by_name::<Option<gst::TagList>>(
+        &format!("get-{}-tags", kind.playbin_name()),
+        &[&index],
+    )
+}
+
+pub(crate) fn current_track(pipeline: &gst::Pipeline, kind: TrackKind) -> Option<usize> {
+    let name = kind.playbin_name();
+    if !track_enabled(pipeline, kind) {
+        return None;
+    }
+    let current = pipeline.property::<i32>(&format!("current-{name}"));
+    usize::try_from(current).ok()
+}
+
+fn track_enabled(pipeline: &gst::Pipeline, kind: TrackKind) -> bool {
+    if !pipeline.has_property("flags", None) {
+        return false;
+    }
+    let flags = pipeline.property_value("flags");
+    glib::FlagsClass::with_type(flags.type_())
+        .is_some_and(|class| class.is_set_by_nick(&flags, kind.playbin_name()))
+}
+
+fn set_track_enabled(
+    pipeline: &gst::Pipeline,
+    kind: TrackKind,
+    enabled: bool,
+) -> Result<(), Error> {
+    let flags = pipeline.property_value("flags");
+    let class = glib::FlagsClass::with_type(flags.type_()).ok_or(Error::Cast)?;
+    let builder = class.builder_with_value(flags).ok_or(Error::Cast)?;
+    let flags = if enabled {
+        builder.set_by_nick(kind.playbin_name())
+    } else {
+        builder.unset_by_nick(kind.playbin_name())
+    }
+    .build()
+    .ok_or(Error::Cast)?;
+    pipeline.set_property_from_value("flags", &flags);
+    Ok(())
+}
+
+pub(crate) fn select_track(
+    pipeline: &gst::Pipeline,
+    kind: TrackKind,
+    index: Option<usize>,
+) -> Result<(), Error> {
+    let name = kind.playbin_name();
+    if !pipeline.has_property(&format!("current-{name}"), None) {
+        return Err(Error::Unsupported);
+    }
+
+    let Some(index) = index else {
+        return set_track_enabled(pipeline, kind, false);
+    };
+    let count = pipeline.property::<i32>(&format!("n-{name}"));
+    let index = i32::try_from(index)
+        .ok()
+        .filter(|index| *index < count)
+        .ok_or(Error::TrackIndex(index))?;
+
+    pipeline.set_property(&format!("current-{name}"), index);
+    set_track_enabled(pipeline, kind, true)
+}
+
+/// Selects the first track of `kind` matching `language`, if any.
+pub(crate) fn select_language(
+    pipeline: &gst::Pipeline,
+    kind: TrackKind,
+    language: &str,
+) -> Result<bool, Error> {
+    let Some(track) = tracks(pipeline, kind).into_iter().find(|track| {
+        track
+            .language
+            .as_deref()
+            .is_some_and(|code| language_matches(code, language))
+    }) else {
+        return Ok(false);
+    };
+    if current_track(pipeline, kind) != Some(track.index) {
+        select_track(pipeline, kind, Some(track.index))?;
+    }
+    Ok(true)
+}
+
+/// Compares language codes loosely, so `de` matches `deu`, `ger` and `de-AT`.
+fn language_matches(code: &str, preferred: &str) -> bool {
+    normalize_language(code) == normalize_language(preferred)
+}
+
+/// Maps ISO 639-1, 639-2/T and 639-2/B codes to ISO 639-1, ignoring any region.
+/// Codes without a two-letter equivalent are kept as they are.
+fn normalize_language(code: &str) -> String {
+    let primary = code
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    gst_tag::language_codes::language_code_iso_639_1(&primary)
+        .map(|code| code.to_string())
+        .unwrap_or(primary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn language_codes_match_across_standards() {
+        gst::init().unwrap();
+        let cases = [
+            ("en", "eng", true),
+            ("eng", "en-US", true),
+            ("en_GB", "en", true),
+            ("ja", "jpn", true),
+            ("jpn", "ja-JP", true),
+            ("es", "spa", true),
+            ("sv", "swe", true),
+            ("pl", "pol", true),
+            ("cs", "ces", true),
+            ("cze", "cs", true),
+            ("de", "ger", true),
+            ("deu", "ger", true),
+            ("fr", "fre", true),
+            ("zh", "chi", true),
+            ("nl", "dut", true),
+            ("en", "de", false),
+            ("ger", "fr", false),
+            ("spa", "sv", false),
+        ];
+        for (code, preferred, expected) in cases {
+            assert_eq!(
+                language_matches(code, preferred),
+                expected,
+                "{code} vs {preferred}"
+            );
+        }
+    }
+}
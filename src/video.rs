@@ -1,16 +1,38 @@
 // This file is taken nearly one to one from https://github.com/jazzfool/iced_video_player
+use crate::audio::{
+    self, Analysis, AudioDevice, AudioFilter, AudioLevels, AudioSpectrum, Equalizer,
+};
+use crate::chapter::{self, Chapter};
+use crate::color::{self, Colorimetry};
+pub use crate::handle::VideoHandle;
+use crate::metadata::{self, MediaMetadata};
+use crate::orientation::Orientation;
+use crate::queue::{self, FrameQueue, FrameStats};
+use crate::subtitle::SubtitleCue;
+use crate::track::{self, Track, TrackKind, TrackPreferences};
 use crate::Error;
-use cushy::value::{Destination, Dynamic};
+use arc_swap::ArcSwapOption;
+use cushy::kludgine::{wgpu, LazyTexture};
+use cushy::value::{Destination, Dynamic, DynamicReader, Source};
 use glib::property::PropertyGet;
 use gstreamer as gst;
 use gstreamer_app as gst_app;
 use gstreamer_app::prelude::*;
+use gstreamer_video as gst_video;
+use std::collections::VecDeque;
+use std::future::Future;
 use std::num::NonZeroU8;
 use std::ops::{Deref, DerefMut};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
+/// How long the frame worker waits for a sample before checking if the video is still alive.
+const PULL_TIMEOUT: gst::ClockTime = gst::ClockTime::from_mseconds(20);
+
+/// Shortest interval between updates of the position dynamic.
+const MIN_POSITION_INTERVAL: Duration = Duration::from_millis(10);
+
 /// Position in the media.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Position {
@@ -43,6 +65,644 @@ impl From<u64> for Position {
     }
 }
 
+/// An event published from the GStreamer bus of a [`Video`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum VideoEvent {
+    /// The stream reached its end.
+    EndOfStream,
+    /// An element of the pipeline reported a fatal error.
+    Error {
+        /// Path of the element that posted the error, if known.
+        source: Option<String>,
+        /// Human readable error message.
+        message: String,
+        /// Additional debug information.
+        debug: Option<String>,
+    },
+    /// An element of the pipeline reported a non-fatal problem.
+    Warning {
+        /// Path of the element that posted the warning, if known.
+        source: Option<String>,
+        /// Human readable warning message.
+        message: String,
+        /// Additional debug information.
+        debug: Option<String>,
+    },
+    /// The pipeline changed its state.
+    StateChanged {
+        /// The state before the change.
+        old: gst::State,
+        /// The state after the change.
+        current: gst::State,
+        /// The state the pipeline is still transitioning to, if any.
+        pending: gst::State,
+    },
+    /// Network buffering progress, in percent. Playback should be paused below `100`.
+    Buffering(i32),
+    /// The media duration changed, usually because it was not known before.
+    DurationChanged(Duration),
+    /// New tags (metadata) were found in the stream.
+    Tags(gst::TagList),
+    /// An asynchronous state change (e.g. a seek) completed.
+    AsyncDone,
+    /// The media reached its end and started over because looping is enabled.
+    Looped,
+    /// The available audio, video or subtitle tracks changed, see [`Video::tracks`].
+    TracksChanged,
+    /// A table of contents was found or updated, see [`Video::chapters`].
+    ChaptersChanged,
+    /// The resolution of the video changed mid-stream.
+    SizeChanged { width: i32, height: i32 },
+    /// An audio output device was plugged in, see [`Video::audio_devices`].
+    AudioDeviceAdded(AudioDevice),
+    /// An audio output device was removed, see [`Video::audio_devices`].
+    /// If it was in use, pick another one with [`Video::set_audio_device`].
+    AudioDeviceRemoved(AudioDevice),
+}
+
+/// Pixel formats the renderer can upload directly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum FrameFormat {
+    #[default]
+    Nv12,
+    I420,
+    /// 16-bit semi-planar, also used for P010 as its samples are stored in the high bits.
+    P016,
+    Rgba,
+    Bgra,
+}
+
+impl FrameFormat {
+    /// Formats the video sink negotiates, the preferred ones first.
+    pub(crate) const SUPPORTED: [gst_video::VideoFormat; 6] = [
+        gst_video::VideoFormat::Nv12,
+        gst_video::VideoFormat::I420,
+        gst_video::VideoFormat::P01010le,
+        gst_video::VideoFormat::P016Le,
+        gst_video::VideoFormat::Rgba,
+        gst_video::VideoFormat::Bgra,
+    ];
+
+    fn from_gst(format: gst_video::VideoFormat) -> Option<Self> {
+        match format {
+            gst_video::VideoFormat::Nv12 => Some(FrameFormat::Nv12),
+            gst_video::VideoFormat::I420 => Some(FrameFormat::I420),
+            gst_video::VideoFormat::P01010le | gst_video::VideoFormat::P016Le => {
+                Some(FrameFormat::P016)
+            }
+            gst_video::VideoFormat::Rgba => Some(FrameFormat::Rgba),
+            gst_video::VideoFormat::Bgra => Some(FrameFormat::Bgra),
+            _ => None,
+        }
+    }
+}
+
+/// A decoded frame, mapped straight from the buffer of the decoder.
+///
+/// The buffer is kept alive until the frame is replaced and uploaded, so no copy is needed.
+#[derive(Debug)]
+pub(crate) struct Frame {
+    video: gst_video::VideoFrame<gst_video::video_frame::Readable>,
+    pub(crate) format: FrameFormat,
+    pub(crate) colorimetry: Colorimetry,
+    /// Peak luminance of HDR video in cd/m², if known from its caps.
+    pub(crate) peak_luminance: Option<f32>,
+    /// Running time the frame is due to be shown at.
+    pub(crate) running_time: Option<gst::ClockTime>,
+    /// Stream time of the frame, as reported by the position of the pipeline.
+    pub(crate) position: Option<gst::ClockTime>,
+}
+
+impl Frame {
+    pub(crate) fn new(
+        buffer: gst::Buffer,
+        info: &gst_video::VideoInfo,
+        peak_luminance: Option<f32>,
+        running_time: Option<gst::ClockTime>,
+        position: Option<gst::ClockTime>,
+    ) -> Result<Self, Error> {
+        let format = FrameFormat::from_gst(info.format()).ok_or(Error::Caps)?;
+        let video =
+            gst_video::VideoFrame::from_buffer_readable(buffer, info).map_err(|_| Error::Caps)?;
+        Ok(Frame {
+            video,
+            format,
+            colorimetry: Colorimetry::from_info(info),
+            peak_luminance,
+            running_time,
+            position,
+        })
+    }
+
+    pub(crate) fn width(&self) -> u32 {
+        self.video.width()
+    }
+
+    pub(crate) fn height(&self) -> u32 {
+        self.video.height()
+    }
+
+    pub(crate) fn n_planes(&self) -> usize {
+        self.video.n_planes() as usize
+    }
+
+    /// Get the data of each plane, with its bytes per row including any padding.
+    pub(crate) fn planes(&self) -> impl Iterator<Item = (&[u8], u32)> + '_ {
+        (0..self.video.n_planes()).filter_map(|plane| {
+            let data = self.video.plane_data(plane).ok()?;
+            Some((data, self.video.plane_stride()[plane as usize] as u32))
+        })
+    }
+}
+
+/// Lock-free slot holding the latest decoded frame, shared between the decoder thread and the renderer.
+pub(crate) type FrameSlot = ArcSwapOption<Frame>;
+
+fn caps_info(caps: &gst::CapsRef) -> Result<gst_video::VideoInfo, Error> {
+    gst_video::VideoInfo::from_caps(caps).map_err(|_| Error::Caps)
+}
+
+/// The overall state of the playback, see [`Video::state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PlayerState {
+    /// The media is being opened or prerolled.
+    #[default]
+    Loading,
+    /// Playback is stalled while network data is buffered.
+    Buffering,
+    /// The media is playing.
+    Playing,
+    /// The media is paused.
+    Paused,
+    /// The media reached its end.
+    Ended,
+    /// The pipeline reported an error, see [`VideoEvent::Error`].
+    Error,
+}
+
+/// How frames are decoded during fast or reverse playback, see [`Video::set_trick_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TrickMode {
+    /// Decode and show every frame, with audio.
+    #[default]
+    Off,
+    /// Let decoders skip frames they can't keep up with, without audio.
+    Skip,
+    /// Only decode key frames, without audio. The cheapest way to scan at high rates.
+    KeyUnits,
+}
+
+impl TrickMode {
+    fn seek_flags(self) -> gst::SeekFlags {
+        match self {
+            TrickMode::Off => gst::SeekFlags::empty(),
+            TrickMode::Skip => gst::SeekFlags::TRICKMODE | gst::SeekFlags::TRICKMODE_NO_AUDIO,
+            TrickMode::KeyUnits => {
+                gst::SeekFlags::TRICKMODE
+                    | gst::SeekFlags::TRICKMODE_KEY_UNITS
+                    | gst::SeekFlags::TRICKMODE_NO_AUDIO
+            }
+        }
+    }
+
+    /// Accurate seeks would decode the frames key unit scanning skips.
+    fn accuracy_flag(self) -> gst::SeekFlags {
+        match self {
+            TrickMode::KeyUnits => gst::SeekFlags::KEY_UNIT,
+            TrickMode::Off | TrickMode::Skip => gst::SeekFlags::ACCURATE,
+        }
+    }
+
+    /// The mode to try next if the demuxer rejects this one.
+    fn fallback(self) -> Option<Self> {
+        match self {
+            TrickMode::KeyUnits => Some(TrickMode::Skip),
+            TrickMode::Skip => Some(TrickMode::Off),
+            TrickMode::Off => None,
+        }
+    }
+}
+
+/// Steps `frames` forward while paused, returns `false` if the pipeline doesn't handle steps.
+fn step_forward(source: &gst::Pipeline, frames: u64) -> bool {
+    let step = gst::event::Step::new(gst::format::Buffers::from_u64(frames), 1.0, true, false);
+    source.send_event(step)
+}
+
+/// Checks if `source` is paused with no state change or preroll going on.
+fn is_settled_paused(source: &gst::Pipeline) -> bool {
+    matches!(
+        source.state(gst::ClockTime::ZERO),
+        (
+            Ok(gst::StateChangeSuccess::Success),
+            gst::State::Paused,
+            gst::State::VoidPending
+        )
+    )
+}
+
+/// Seeks `source` back to the start of the media, keeping the playback direction of `rate`.
+fn seek_to_start(source: &gst::Pipeline, rate: f64, flags: gst::SeekFlags) -> Result<(), Error> {
+    source.seek(
+        rate,
+        flags,
+        gst::SeekType::Set,
+        gst::ClockTime::ZERO,
+        gst::SeekType::End,
+        gst::ClockTime::ZERO,
+    )?;
+    Ok(())
+}
+
+/// Get the start and stop of a seek to `position`. Forward playback runs from `position`
+/// to `end`, reverse playback from `start` up to `position`.
+fn seek_range<T>(rate: f64, position: T, start: T, end: T) -> (T, T) {
+    if rate < 0.0 {
+        (start, position)
+    } else {
+        (position, end)
+    }
+}
+
+/// Seeks to `position` playing in the direction of `rate` until the end (or start) of the media.
+fn seek_from(
+    source: &gst::Pipeline,
+    rate: f64,
+    flags: gst::SeekFlags,
+    position: gst::ClockTime,
+) -> Result<(), Error> {
+    let ((start_type, start), (stop_type, stop)) = seek_range(
+        rate,
+        (gst::SeekType::Set, position),
+        (gst::SeekType::Set, gst::ClockTime::ZERO),
+        (gst::SeekType::End, gst::ClockTime::ZERO),
+    );
+    source.seek(rate, flags, start_type, start, stop_type, stop)?;
+    Ok(())
+}
+
+/// What [`Internal::reload_audio`] restores once the pipeline has prerolled again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AudioReload {
+    position: gst::ClockTime,
+    /// The selected tracks, as going to `Ready` resets them.
+    tracks: [(TrackKind, Option<usize>); 2],
+}
+
+fn apply_track_preferences(source: &gst::Pipeline, preferences: &TrackPreferences) {
+    for (kind, language) in [
+        (TrackKind::Audio, &preferences.audio),
+        (TrackKind::Subtitle, &preferences.subtitle),
+    ] {
+        if let Some(language) = language {
+            if let Err(err) = track::select_language(source, kind, language) {
+                log::warn!("failed to select preferred {kind:?} language: {err}");
+            }
+        }
+    }
+}
+
+/// Drains the pipeline bus on its own thread and republishes messages as [`VideoEvent`]s.
+struct BusWorker {
+    source: gst::Pipeline,
+    events: Dynamic<Option<VideoEvent>>,
+    is_eos: Dynamic<bool>,
+    looping: Arc<AtomicBool>,
+    segment_looping: Arc<AtomicBool>,
+    speed: Dynamic<f64>,
+    trick_mode: Dynamic<TrickMode>,
+    state: Dynamic<PlayerState>,
+    position: Dynamic<Duration>,
+    position_interval: Dynamic<Duration>,
+    duration: Dynamic<Duration>,
+    track_preferences: Dynamic<TrackPreferences>,
+    metadata: Dynamic<MediaMetadata>,
+    orientation: Dynamic<Orientation>,
+    chapters: Dynamic<Vec<Chapter>>,
+    current_chapter: Dynamic<Option<usize>>,
+    audio_levels: Dynamic<AudioLevels>,
+    audio_spectrum: Dynamic<AudioSpectrum>,
+    /// Level and spectrum results with the running time their audio ends at.
+    analysis: VecDeque<(gst::ClockTime, Analysis)>,
+    audio_reload: Dynamic<Option<AudioReload>>,
+    pending_step: Dynamic<Option<u64>>,
+    paused: Dynamic<bool>,
+    buffering: bool,
+}
+
+impl BusWorker {
+    fn spawn(mut self, bus: gst::Bus, alive: Arc<AtomicBool>) -> std::thread::JoinHandle<()> {
+        std::thread::spawn(move || {
+            let mut last_position_update = Instant::now();
+            while alive.load(Ordering::Acquire) {
+                let interval = self.position_interval.get();
+                let next_analysis = self.publish_analysis().unwrap_or(Duration::MAX);
+                let timeout = interval.min(Duration::from_millis(100)).min(next_analysis);
+                if let Some(msg) =
+                    bus.timed_pop(gst::ClockTime::from_nseconds(timeout.as_nanos() as _))
+                {
+                    self.handle(&msg);
+                }
+
+                if last_position_update.elapsed() >= interval {
+                    last_position_update = Instant::now();
+                    if let Some(position) = self.source.query_position::<gst::ClockTime>() {
+                        let position = Duration::from_nanos(position.nseconds());
+                        self.position.set(position);
+                        self.current_chapter.set(
+                            self.chapters
+                                .map_ref(|chapters| chapter::at(chapters, position)),
+                        );
+                    }
+                }
+            }
+        })
+    }
+
+    /// Publishes the analysis results of the audio heard by now. Returns the time until
+    /// the next one is due, `None` if there is none or the pipeline isn't playing.
+    fn publish_analysis(&mut self) -> Option<Duration> {
+        let now = queue::running_time(&self.source)?;
+        // the audio reaches the speakers after the latency of the pipeline
+        let latency = self.source.latency().unwrap_or(gst::ClockTime::ZERO);
+        let (mut levels, mut spectrum) = (None, None);
+        let mut next = None;
+        while let Some(&(ends_at, _)) = self.analysis.front() {
+            let heard_at = ends_at + latency;
+            if heard_at > now {
+                next = Some(Duration::from(heard_at - now));
+                break;
+            }
+            match self.analysis.pop_front().map(|(_, analysis)| analysis) {
+                Some(Analysis::Levels(new)) => levels = Some(new),
+                Some(Analysis::Spectrum(new)) => spectrum = Some(new),
+                None => {}
+            }
+        }
+        // only the latest results are of interest if several were due
+        if let Some(levels) = levels {
+            self.audio_levels.set(levels);
+        }
+        if let Some(spectrum) = spectrum {
+            self.audio_spectrum.set(spectrum);
+        }
+        next
+    }
+
+    fn is_from_source(&self, msg: &gst::Message) -> bool {
+        msg.src()
+            .is_some_and(|src| src == self.source.upcast_ref::<gst::Object>())
+    }
+
+    fn loop_to_start(&self, flags: gst::SeekFlags) -> Result<(), Error> {
+        seek_to_start(
+            &self.source,
+            self.speed.get(),
+            flags | self.trick_mode.get().seek_flags(),
+        )
+    }
+
+    fn update_state(&mut self, event: &VideoEvent) {
+        let state = match event {
+            VideoEvent::EndOfStream => PlayerState::Ended,
+            VideoEvent::Error { .. } => PlayerState::Error,
+            VideoEvent::Buffering(percent) => {
+                self.buffering = *percent < 100;
+                if self.buffering {
+                    PlayerState::Buffering
+                } else if self.source.current_state() == gst::State::Playing {
+                    PlayerState::Playing
+                } else {
+                    PlayerState::Paused
+                }
+            }
+            VideoEvent::StateChanged {
+                current, pending, ..
+            } => match (current, pending) {
+                _ if self.buffering => PlayerState::Buffering,
+                (gst::State::Playing, _) => PlayerState::Playing,
+                (gst::State::Paused, gst::State::VoidPending) => PlayerState::Paused,
+                // flushing seeks go through paused, which isn't worth reporting
+                (gst::State::Paused, _) => return,
+                _ => PlayerState::Loading,
+            },
+            VideoEvent::Looped => PlayerState::Playing,
+            _ => return,
+        };
+
+        // keep errors visible until playback actually recovers
+        if self.state.get() == PlayerState::Error && state != PlayerState::Playing {
+            return;
+        }
+        self.state.set(state);
+    }
+
+    fn handle(&mut self, msg: &gst::Message) {
+        let source = || msg.src().map(|src| src.path_string().to_string());
+        let event = match msg.view() {
+            gst::MessageView::SegmentDone(_) => {
+                if self.looping.load(Ordering::SeqCst) {
+                    // non-flushing, so the next loop is queued right behind the current data
+                    match self.loop_to_start(gst::SeekFlags::SEGMENT) {
+                        Ok(()) => VideoEvent::Looped,
+                        Err(err) => {
+                            log::error!("failed to loop stream: {err}");
+                            return;
+                        }
+                    }
+                } else {
+                    // looping was turned off during the last loop, finish like a normal stream
+                    self.segment_looping.store(false, Ordering::SeqCst);
+                    self.source.send_event(gst::event::Eos::new());
+                    return;
+                }
+            }
+            gst::MessageView::Eos(_) => {
+                // fallback for sources which can't do segment seeks
+                if self.looping.load(Ordering::SeqCst) {
+                    match self.loop_to_start(gst::SeekFlags::FLUSH) {
+                        Ok(()) => VideoEvent::Looped,
+                        Err(err) => {
+                            log::error!("failed to restart stream: {err}");
+                            self.is_eos.set(true);
+                            VideoEvent::EndOfStream
+                        }
+                    }
+                } else {
+                    self.is_eos.set(true);
+                    VideoEvent::EndOfStream
+                }
+            }
+            gst::MessageView::Error(err) => {
+                log::error!("gstreamer error from {:?}: {}", source(), err.error());
+                VideoEvent::Error {
+                    source: source(),
+                    message: err.error().to_string(),
+                    debug: err.debug().map(|debug| debug.to_string()),
+                }
+            }
+            gst::MessageView::Warning(warning) => {
+                log::warn!("gstreamer warning from {:?}: {}", source(), warning.error());
+                VideoEvent::Warning {
+                    source: source(),
+                    message: warning.error().to_string(),
+                    debug: warning.debug().map(|debug| debug.to_string()),
+                }
+            }
+            // state changes of every child element are posted too, only the pipeline is interesting
+            gst::MessageView::StateChanged(state) if self.is_from_source(msg) => {
+                VideoEvent::StateChanged {
+                    old: state.old(),
+                    current: state.current(),
+                    pending: state.pending(),
+                }
+            }
+            gst::MessageView::Buffering(buffering) => VideoEvent::Buffering(buffering.percent()),
+            gst::MessageView::DurationChanged(_) => {
+                let duration = Duration::from_nanos(
+                    self.source
+                        .query_duration::<gst::ClockTime>()
+                        .map_or(0, |duration| duration.nseconds()),
+                );
+                self.duration.set(duration);
+                VideoEvent::DurationChanged(duration)
+            }
+            gst::MessageView::Tag(tag) => {
+                let tags = tag.tags();
+                self.merge_tags(&tags);
+                self.update_stream_bitrates();
+                VideoEvent::Tags(tags)
+            }
+            gst::MessageView::AsyncDone(_) if self.is_from_source(msg) => {
+                self.finish_audio_reload();
+                self.finish_step();
+                VideoEvent::AsyncDone
+            }
+            gst::MessageView::Toc(toc) => {
+                let (toc, _updated) = toc.toc();
+                self.chapters.set(chapter::from_toc(&toc));
+                VideoEvent::ChaptersChanged
+            }
+            // posted often, so they are only published through their own dynamics
+            gst::MessageView::Element(element) => {
+                // posted as the audio passes the filter, so held back until it's heard
+                if let Some((ends_at, analysis)) = element.structure().and_then(audio::analysis) {
+                    // a flush, e.g. by a seek, starts the running time over
+                    if self
+                        .analysis
+                        .back()
+                        .is_some_and(|(last, _)| ends_at < *last)
+                    {
+                        self.analysis.clear();
+                    }
+                    self.analysis.push_back((ends_at, analysis));
+                }
+                return;
+            }
+            gst::MessageView::Application(app)
+                if app
+                    .structure()
+                    .is_some_and(|s| s.name() == track::TRACKS_CHANGED) =>
+            {
+                apply_track_preferences(&self.source, &self.track_preferences.get());
+                // stream tags which were found before the pipeline posted them
+                for kind in [TrackKind::Video, TrackKind::Audio] {
+                    if let Some(tags) = track::current_tags(&self.source, kind) {
+                        self.merge_tags(&tags);
+                    }
+                }
+                self.update_stream_bitrates();
+                VideoEvent::TracksChanged
+            }
+            _ => return,
+        };
+
+        self.update_state(&event);
+
+        // `map_mut` always notifies, so repeated identical events (e.g. buffering) are not lost
+        self.events.map_mut(|mut e| *e = Some(event));
+    }
+
+    /// Restores the position and tracks once the pipeline prerolled after [`Internal::reload_audio`].
+    fn finish_audio_reload(&self) {
+        let Some(reload) = self.audio_reload.replace(None).flatten() else {
+            return;
+        };
+        for (kind, index) in reload.tracks {
+            // disabled tracks stay disabled, as the flags aren't reset
+            if index.is_some() {
+                if let Err(err) = track::select_track(&self.source, kind, index) {
+                    log::warn!("failed to restore the {kind:?} track: {err}");
+                }
+            }
+        }
+
+        let trick_mode = self.trick_mode.get();
+        let segment = if self.segment_looping.load(Ordering::SeqCst) {
+            gst::SeekFlags::SEGMENT
+        } else {
+            gst::SeekFlags::empty()
+        };
+        let flags =
+            gst::SeekFlags::FLUSH | segment | trick_mode.accuracy_flag() | trick_mode.seek_flags();
+        if let Err(err) = seek_from(&self.source, self.speed.get(), flags, reload.position) {
+            log::error!("failed to restore the position after reloading the audio: {err}");
+        }
+        if !self.paused.get() {
+            if let Err(err) = self.source.set_state(gst::State::Playing) {
+                log::error!("failed to resume after reloading the audio: {err}");
+            }
+        }
+    }
+
+    /// Sends the step queued by [`Internal::step_frames`] once pausing has finished.
+    fn finish_step(&self) {
+        if !is_settled_paused(&self.source) {
+            return;
+        }
+        let Some(frames) = self.pending_step.replace(None).flatten() else {
+            return;
+        };
+        if !step_forward(&self.source, frames) {
+            log::warn!("failed to step {frames} frames");
+        }
+    }
+
+    /// Reads the bitrates from the tags `playbin` keeps per stream, as tag messages
+    /// don't tell which stream they belong to.
+    fn update_stream_bitrates(&self) {
+        let video = track::current_tags(&self.source, TrackKind::Video);
+        let audio = track::current_tags(&self.source, TrackKind::Audio);
+        let bitrates = (
+            video.as_ref().and_then(metadata::bitrate),
+            audio.as_ref().and_then(metadata::bitrate),
+        );
+        let changed = self
+            .metadata
+            .map_ref(|metadata| (metadata.video_bitrate, metadata.audio_bitrate) != bitrates);
+        if changed {
+            self.metadata.map_mut(|mut metadata| {
+                metadata.set_stream_bitrates(video.as_ref(), audio.as_ref())
+            });
+        }
+    }
+
+    fn merge_tags(&self, tags: &gst::TagList) {
+        // bitrate tags arrive every few buffers, observers are only notified of changes
+        let metadata_tags = metadata::without_bitrates(tags);
+        if self
+            .metadata
+            .map_ref(|metadata| metadata.is_changed_by(&metadata_tags))
+        {
+            self.metadata
+                .map_mut(|mut metadata| metadata.merge(&metadata_tags));
+        }
+        if let Some(orientation) = Orientation::from_tags(tags) {
+            self.orientation.set(orientation);
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct Internal {
     pub(crate) id: u64,
@@ -51,147 +711,540 @@ pub(crate) struct Internal {
     pub(crate) source: gst::Pipeline,
     pub(crate) alive: Arc<AtomicBool>,
     pub(crate) worker: Option<std::thread::JoinHandle<()>>,
+    pub(crate) bus_worker: Option<std::thread::JoinHandle<()>>,
 
-    pub(crate) width: i32,
-    pub(crate) height: i32,
+    pub(crate) size: Dynamic<(i32, i32)>,
+    pub(crate) colorimetry: Dynamic<Colorimetry>,
+    pub(crate) colorimetry_override: Dynamic<Option<Colorimetry>>,
     pub(crate) framerate: f64,
-    pub(crate) duration: Duration,
-    pub(crate) speed: f64,
-    pub(crate) sync_av: bool,
-
-    pub(crate) frame: Arc<Mutex<Vec<u8>>>,
-    pub(crate) last_frame_time: Arc<Mutex<Instant>>,
-    pub(crate) looping: bool,
-    pub(crate) is_eos: bool,
-    pub(crate) restart_stream: bool,
-    pub(crate) sync_av_avg: u64,
-    pub(crate) sync_av_counter: u64,
+    pub(crate) duration: Dynamic<Duration>,
+    pub(crate) speed: Dynamic<f64>,
+    pub(crate) trick_mode: Dynamic<TrickMode>,
+    pub(crate) preserve_pitch: Dynamic<bool>,
+    pub(crate) audio_analysis: Dynamic<bool>,
+    pub(crate) equalizer: Dynamic<Equalizer>,
+    pub(crate) balance: Dynamic<f32>,
+    pub(crate) mono: Dynamic<bool>,
+    pub(crate) audio_filter: AudioFilter,
+    audio_reload: Dynamic<Option<AudioReload>>,
+    /// Frames to step forward once the pipeline has paused, see [`Internal::step_frames`].
+    pending_step: Dynamic<Option<u64>>,
+    pub(crate) audio_levels: Dynamic<AudioLevels>,
+    pub(crate) audio_spectrum: Dynamic<AudioSpectrum>,
+    pub(crate) audio_device: Dynamic<Option<AudioDevice>>,
+    pub(crate) audio_devices: Dynamic<Vec<AudioDevice>>,
+    pub(crate) device_monitor: Option<gst::DeviceMonitor>,
+
+    pub(crate) frame: Arc<FrameSlot>,
+    pub(crate) frames: Arc<FrameQueue>,
+    pub(crate) frame_stats: Dynamic<FrameStats>,
+    pub(crate) looping: Arc<AtomicBool>,
+    pub(crate) segment_looping: Arc<AtomicBool>,
+    pub(crate) is_eos: Dynamic<bool>,
 
     pub(crate) upload_frame: Dynamic<()>,
-    pub(crate) subtitles: Dynamic<Option<String>>,
+    pub(crate) subtitles: Dynamic<Option<SubtitleCue>>,
+    pub(crate) events: Dynamic<Option<VideoEvent>>,
+    pub(crate) state: Dynamic<PlayerState>,
+    pub(crate) position: Dynamic<Duration>,
+    pub(crate) position_interval: Dynamic<Duration>,
+    pub(crate) paused: Dynamic<bool>,
+    pub(crate) muted: Dynamic<bool>,
+    pub(crate) volume: Dynamic<f64>,
+    pub(crate) track_preferences: Dynamic<TrackPreferences>,
+    pub(crate) metadata: Dynamic<MediaMetadata>,
+    pub(crate) orientation: Dynamic<Orientation>,
+    pub(crate) orientation_override: Dynamic<Option<Orientation>>,
+    pub(crate) chapters: Dynamic<Vec<Chapter>>,
+    pub(crate) current_chapter: Dynamic<Option<usize>>,
 }
 
 impl Internal {
     pub(crate) fn seek(&self, position: impl Into<Position>, accurate: bool) -> Result<(), Error> {
         let position = position.into();
+        let trick_mode = self.trick_mode.get();
+        let flags = gst::SeekFlags::FLUSH
+            | self.segment_flag()
+            | trick_mode.seek_flags()
+            | if accurate {
+                trick_mode.accuracy_flag()
+            } else {
+                gst::SeekFlags::empty()
+            };
 
         // gstreamer complains if the start & end value types aren't the same
-        match &position {
-            Position::Time(_) => self.source.seek(
-                self.speed,
-                gst::SeekFlags::FLUSH
-                    | if accurate {
-                        gst::SeekFlags::ACCURATE
-                    } else {
-                        gst::SeekFlags::empty()
-                    },
-                gst::SeekType::Set,
-                gst::GenericFormattedValue::from(position),
-                gst::SeekType::Set,
-                gst::ClockTime::NONE,
-            )?,
-            Position::Frame(_) => self.source.seek(
-                self.speed,
-                gst::SeekFlags::FLUSH
-                    | if accurate {
-                        gst::SeekFlags::ACCURATE
-                    } else {
-                        gst::SeekFlags::empty()
-                    },
-                gst::SeekType::Set,
-                gst::GenericFormattedValue::from(position),
-                gst::SeekType::Set,
-                gst::format::Default::NONE,
-            )?,
+        let (start, end) = match &position {
+            Position::Time(_) => (
+                gst::GenericFormattedValue::Time(Some(gst::ClockTime::ZERO)),
+                gst::GenericFormattedValue::Time(None),
+            ),
+            Position::Frame(_) => (
+                gst::GenericFormattedValue::Default(Some(gst::format::Default::ZERO)),
+                gst::GenericFormattedValue::Default(None),
+            ),
         };
+        let position = gst::GenericFormattedValue::from(position);
+
+        // reverse playback runs from the stop position towards the start
+        let rate = self.speed.get();
+        let (start, stop) = seek_range(rate, position, start, end);
+        self.source.seek(
+            rate,
+            flags,
+            gst::SeekType::Set,
+            start,
+            gst::SeekType::Set,
+            stop,
+        )?;
 
         Ok(())
     }
 
+    /// Keeps segment seeks going while looping, otherwise a seek would end the loop.
+    fn segment_flag(&self) -> gst::SeekFlags {
+        if self.segment_looping.load(Ordering::SeqCst) {
+            gst::SeekFlags::SEGMENT
+        } else {
+            gst::SeekFlags::empty()
+        }
+    }
+
+    /// Seeks to `position` playing in the direction of `rate` until the end (or start) of the media.
+    fn seek_from(
+        &self,
+        rate: f64,
+        flags: gst::SeekFlags,
+        position: gst::ClockTime,
+    ) -> Result<(), Error> {
+        let flags = flags | self.segment_flag() | self.trick_mode.get().seek_flags();
+        seek_from(&self.source, rate, flags, position)
+    }
+
     pub(crate) fn set_speed(&mut self, speed: f64) -> Result<(), Error> {
+        self.set_trick_mode(speed, TrickMode::Off).map(|_| ())
+    }
+
+    /// Changes the playback rate with the given trick mode, falling back to simpler modes
+    /// the demuxer supports. Returns the mode that is used.
+    pub(crate) fn set_trick_mode(
+        &mut self,
+        rate: f64,
+        mode: TrickMode,
+    ) -> Result<TrickMode, Error> {
         let Some(position) = self.source.query_position::<gst::ClockTime>() else {
             return Err(Error::Caps);
         };
-        if speed > 0.0 {
-            self.source.seek(
-                speed,
-                gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE,
-                gst::SeekType::Set,
-                position,
-                gst::SeekType::End,
-                gst::ClockTime::from_seconds(0),
-            )?;
+
+        let previous = self.trick_mode.get();
+        let mut next = Some(mode);
+        while let Some(mode) = next {
+            // `seek_from` picks up the trick mode flags
+            self.trick_mode.set(mode);
+            match self.seek_from(rate, gst::SeekFlags::FLUSH | mode.accuracy_flag(), position) {
+                Ok(()) => {
+                    // only committed once the pipeline took it
+                    self.speed.set(rate);
+                    return Ok(mode);
+                }
+                Err(err) => {
+                    log::debug!("trick mode {mode:?} at rate {rate} unsupported: {err}");
+                    next = mode.fallback();
+                }
+            }
+        }
+
+        self.trick_mode.set(previous);
+        Err(Error::Unsupported)
+    }
+
+    /// Returns `true` if the stream had ended and starts over, the caller then resets
+    /// `is_eos` once the lock is released, as that notifies its observers.
+    pub(crate) fn set_looping(&mut self, looping: bool) -> bool {
+        self.looping.store(looping, Ordering::SeqCst);
+        if !looping || self.segment_looping.load(Ordering::SeqCst) {
+            // a running segment loop finishes with EOS once it reaches the end
+            return false;
+        }
+
+        // Segment seeks make the demuxer post `SegmentDone` instead of EOS at the end, so the
+        // next loop can be queued without flushing. This avoids a black frame or audio gap.
+        let ended = self.is_eos.get();
+        let position = if ended {
+            gst::ClockTime::ZERO
         } else {
-            self.source.seek(
-                speed,
-                gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE,
-                gst::SeekType::Set,
-                gst::ClockTime::from_seconds(0),
-                gst::SeekType::Set,
-                position,
-            )?;
-        }
-        self.speed = speed;
+            self.source
+                .query_position::<gst::ClockTime>()
+                .unwrap_or(gst::ClockTime::ZERO)
+        };
+        self.segment_looping.store(true, Ordering::SeqCst);
+        if let Err(err) = self.seek_from(
+            self.speed.get(),
+            gst::SeekFlags::FLUSH | self.trick_mode.get().accuracy_flag(),
+            position,
+        ) {
+            // the bus worker restarts the stream on EOS instead
+            log::debug!("segment seek unsupported, falling back to restarting on EOS: {err}");
+            self.segment_looping.store(false, Ordering::SeqCst);
+        }
+        ended
+    }
+
+    pub(crate) fn set_preserve_pitch(&mut self, preserve_pitch: bool) -> Result<(), Error> {
+        if !self.source.has_property("audio-filter", None) {
+            return Err(Error::Unsupported);
+        }
+        self.preserve_pitch.set(preserve_pitch);
+        self.apply_audio_processing()
+    }
+
+    pub(crate) fn set_audio_analysis(&mut self, analysis: bool) -> Result<(), Error> {
+        if !self.source.has_property("audio-filter", None) {
+            return Err(Error::Unsupported);
+        }
+        if self.audio_analysis.replace(analysis).is_none() {
+            return Ok(());
+        }
+        if !analysis {
+            self.audio_levels.set(AudioLevels::default());
+            self.audio_spectrum.set(AudioSpectrum::default());
+        }
+        self.reload_audio()
+    }
+
+    pub(crate) fn set_equalizer(&mut self, equalizer: Equalizer) -> Result<(), Error> {
+        if !self.source.has_property("audio-filter", None) {
+            return Err(Error::Unsupported);
+        }
+        self.equalizer.set(equalizer);
+        self.apply_audio_processing()
+    }
+
+    pub(crate) fn set_balance(&mut self, balance: f32) -> Result<(), Error> {
+        if !self.source.has_property("audio-filter", None) {
+            return Err(Error::Unsupported);
+        }
+        self.balance.set(balance.clamp(-1.0, 1.0));
+        self.apply_audio_processing()
+    }
+
+    pub(crate) fn set_mono(&mut self, mono: bool) -> Result<(), Error> {
+        if !self.source.has_property("audio-filter", None) {
+            return Err(Error::Unsupported);
+        }
+        self.mono.set(mono);
+        self.apply_audio_processing()
+    }
+
+    /// The filter chain needed by the current options. Processing elements already in the chain
+    /// are kept when their feature is turned off, so they can be adjusted again without a reload.
+    /// `scaletempo` is the exception, as it keeps the pitch for as long as it's there.
+    fn wanted_audio_filter(&self) -> AudioFilter {
+        AudioFilter {
+            preserve_pitch: self.preserve_pitch.get(),
+            equalizer: self.audio_filter.equalizer || !self.equalizer.get().is_flat(),
+            mono: self.audio_filter.mono || self.mono.get(),
+            balance: self.audio_filter.balance || self.balance.get() != 0.0,
+            analysis: self.audio_analysis.get(),
+        }
+    }
+
+    /// Applies the pitch, equalizer, balance and mono options, reloading the audio path only
+    /// if the chain is missing an element for them.
+    fn apply_audio_processing(&mut self) -> Result<(), Error> {
+        if self.wanted_audio_filter() != self.audio_filter {
+            return self.reload_audio();
+        }
+        if let Some(filter) = self.source.property::<Option<gst::Element>>("audio-filter") {
+            self.configure_audio_filter(&filter);
+        }
         Ok(())
     }
 
-    pub(crate) fn restart_stream(&mut self) -> Result<(), Error> {
-        self.is_eos = false;
-        self.set_paused(false);
-        self.seek(0, false)?;
+    fn configure_audio_filter(&self, filter: &gst::Element) {
+        audio::configure(
+            filter,
+            &self.equalizer.get(),
+            self.balance.get(),
+            self.mono.get(),
+        );
+    }
+
+    pub(crate) fn audio_devices(&mut self) -> Result<DynamicReader<Vec<AudioDevice>>, Error> {
+        if self.device_monitor.is_none() {
+            self.device_monitor = Some(audio::monitor_devices(
+                self.audio_devices.clone(),
+                self.events.clone(),
+            )?);
+        }
+        Ok(self.audio_devices.clone().into_reader())
+    }
+
+    pub(crate) fn set_audio_device(&mut self, device: Option<AudioDevice>) -> Result<(), Error> {
+        if !self.source.has_property("audio-sink", None) {
+            return Err(Error::Unsupported);
+        }
+        if self.audio_device.replace(device).is_none() {
+            return Ok(());
+        }
+        self.reload_audio()
+    }
+
+    /// Replaces the `audio-filter` and `audio-sink` of `playbin` with ones matching the current options.
+    /// Doesn't wait for the pipeline to preroll again, the bus worker restores the position
+    /// and tracks then.
+    fn reload_audio(&mut self) -> Result<(), Error> {
+        let filter = self.wanted_audio_filter();
+        let element = filter.build();
+        if let Some(element) = &element {
+            self.configure_audio_filter(element);
+        }
+        // `None` lets playbin pick the default output
+        let sink = self
+            .audio_device
+            .get()
+            .map(|device| device.create_sink())
+            .transpose()?;
+
+        // playbin only picks up a new filter and sink when it sets up the audio path again
+        let position = self
+            .source
+            .query_position::<gst::ClockTime>()
+            .unwrap_or(gst::ClockTime::ZERO);
+        let tracks = [TrackKind::Audio, TrackKind::Subtitle]
+            .map(|kind| (kind, track::current_track(&self.source, kind)));
+        self.source.set_state(gst::State::Ready)?;
+        self.source.set_property("audio-filter", element);
+        self.source.set_property("audio-sink", sink);
+        self.audio_filter = filter;
+        // the bus worker seeks back and resumes once the pipeline has prerolled
+        self.audio_reload
+            .set(Some(AudioReload { position, tracks }));
+        self.source.set_state(gst::State::Paused)?;
         Ok(())
     }
 
-    pub(crate) fn set_paused(&mut self, paused: bool) {
-        self.source
-            .set_state(if paused {
-                gst::State::Paused
-            } else {
-                gst::State::Playing
+    /// Makes the `paused`, `volume` and `muted` dynamics drive the pipeline.
+    fn bind_controls(&self) {
+        let source = self.source.clone();
+        let is_eos = self.is_eos.clone();
+        let segment_looping = Arc::clone(&self.segment_looping);
+        let speed = self.speed.clone();
+        let trick_mode = self.trick_mode.clone();
+        let state = self.state.clone();
+        let frames = Arc::clone(&self.frames);
+        let frame = Arc::clone(&self.frame);
+        let pending_step = self.pending_step.clone();
+        // called on whichever thread set the dynamic, so failures are reported instead of panicking
+        self.paused
+            .for_each_subsequent(move |paused| {
+                let target = if *paused {
+                    gst::State::Paused
+                } else {
+                    gst::State::Playing
+                };
+                let was_playing = source.current_state() == gst::State::Playing;
+                if let Err(err) = source.set_state(target) {
+                    log::error!("failed to change the state to {target:?}: {err}");
+                    state.set(PlayerState::Error);
+                    return;
+                }
+                let segment = if segment_looping.load(Ordering::SeqCst) {
+                    gst::SeekFlags::SEGMENT
+                } else {
+                    gst::SeekFlags::empty()
+                };
+
+                // the decoder ran ahead to fill the frame queue, go back to the frame on screen
+                // so it's prerolled and the position matches it
+                if *paused && was_playing {
+                    frames.clear();
+                    let shown = frame.load().as_ref().and_then(|frame| frame.position);
+                    if let Some(shown) = shown {
+                        let mode = trick_mode.get();
+                        let flags = gst::SeekFlags::FLUSH
+                            | mode.accuracy_flag()
+                            | segment
+                            | mode.seek_flags();
+                        if let Err(err) = seek_from(&source, speed.get(), flags, shown) {
+                            log::debug!("failed to seek to the shown frame: {err}");
+                        }
+                    }
+                }
+
+                // steps still waiting for the pause to finish are void
+                if !*paused {
+                    pending_step.set(None);
+                }
+
+                // Resuming an ended stream starts it over
+                if !*paused && is_eos.replace(false) == Some(true) {
+                    let flags = gst::SeekFlags::FLUSH | segment | trick_mode.get().seek_flags();
+                    if let Err(err) = seek_to_start(&source, speed.get(), flags) {
+                        log::error!("failed to restart stream: {err}");
+                    }
+                }
             })
-            .unwrap(/* state was changed in ctor; state errors caught there */);
+            .persist();
 
-        // Set restart_stream flag to make the stream restart on the next Message::NextFrame
-        if self.is_eos && !paused {
-            self.restart_stream = true;
+        // pipelines other than `playbin` may lack the properties, setting them would panic
+        if !self.source.has_property("volume", None) || !self.source.has_property("mute", None) {
+            return;
         }
+
+        let source = self.source.clone();
+        let muted = self.muted.clone();
+        self.volume
+            .for_each_subsequent(move |volume| {
+                source.set_property("volume", *volume);
+                // for some reason gstreamer unmutes when changing volume?
+                source.set_property("mute", muted.get());
+            })
+            .persist();
+
+        let source = self.source.clone();
+        self.muted
+            .for_each_subsequent(move |muted| source.set_property("mute", *muted))
+            .persist();
     }
 
-    pub(crate) fn paused(&self) -> bool {
-        self.source.state(gst::ClockTime::ZERO).1 == gst::State::Paused
+    pub(crate) fn seek_to_chapter(&self, index: usize) -> Result<(), Error> {
+        let start = self
+            .chapters
+            .map_ref(|chapters| chapters.get(index).map(|chapter| chapter.start))
+            .ok_or(Error::ChapterIndex(index))?;
+        self.seek(start, true)
+    }
+
+    pub(crate) fn next_chapter(&self) -> Result<(), Error> {
+        let position = self.position();
+        let next = self.chapters.map_ref(|chapters| {
+            chapters
+                .iter()
+                .position(|chapter| chapter.start > position)
+                .unwrap_or(chapters.len())
+        });
+        self.seek_to_chapter(next)
     }
 
-    /// Syncs audio with video when there is (inevitably) latency presenting the frame.
-    pub(crate) fn set_av_offset(&mut self, offset: Duration) {
-        if self.sync_av {
-            self.sync_av_counter += 1;
-            self.sync_av_avg = self.sync_av_avg * (self.sync_av_counter - 1) / self.sync_av_counter
-                + offset.as_nanos() as u64 / self.sync_av_counter;
-            if self.sync_av_counter % 128 == 0 {
-                self.source
-                    .set_property("av-offset", -(self.sync_av_avg as i64));
+    pub(crate) fn previous_chapter(&self) -> Result<(), Error> {
+        /// Going back within this time after a chapter start skips to the chapter before it.
+        const RESTART_THRESHOLD: Duration = Duration::from_secs(3);
+
+        let position = self.position();
+        let current = self.chapters.map_ref(|chapters| {
+            chapter::at_or_before(chapters, position).map(|index| (index, chapters[index].start))
+        });
+        match current {
+            Some((index, start)) if position < start + RESTART_THRESHOLD && index > 0 => {
+                self.seek_to_chapter(index - 1)
             }
+            Some((index, _)) => self.seek_to_chapter(index),
+            None => Err(Error::ChapterIndex(0)),
+        }
+    }
+
+    pub(crate) fn step_frames(&mut self, frames: i64) -> Result<(), Error> {
+        if frames == 0 {
+            return Ok(());
         }
+        // the caller pauses first, which finishes asynchronously, and sinks only step once
+        // they have prerolled, so until then the bus worker sends the step on `AsyncDone`
+        if frames > 0 {
+            let frames = frames as u64;
+            self.pending_step
+                .map_mut(|mut step| *step = Some(step.unwrap_or(0) + frames));
+            if !is_settled_paused(&self.source) {
+                return Ok(());
+            }
+            // taken back unless the bus worker was faster
+            let Some(pending) = self.pending_step.replace(None).flatten() else {
+                return Ok(());
+            };
+            if step_forward(&self.source, pending) {
+                return Ok(());
+            }
+        }
+
+        // backward steps, and pipelines not handling step events, seek to the frame instead
+        let frame_duration = Duration::from_secs_f64(1.0 / self.framerate);
+        // step from the frame on screen, the pipeline position may be ahead of it
+        let shown = self
+            .frame
+            .load_full()
+            .and_then(|frame| frame.position)
+            .map_or_else(|| self.position(), Duration::from);
+        let target = shown.as_secs_f64() + frames as f64 / self.framerate;
+        // aim for the middle of the frame, so rounding doesn't land on its neighbour
+        self.seek(
+            Duration::from_secs_f64(target.max(0.0)) + frame_duration / 2,
+            true,
+        )
+    }
+
+    pub(crate) fn position(&self) -> Duration {
+        Duration::from_nanos(
+            self.source
+                .query_position::<gst::ClockTime>()
+                .map_or(0, |pos| pos.nseconds()),
+        )
+    }
+
+    pub(crate) fn paused(&self) -> bool {
+        self.source.state(gst::ClockTime::ZERO).1 == gst::State::Paused
+    }
+
+    /// Moves the frame due at the current running time to the slot the renderer uploads from.
+    /// Returns `false` if no new frame is due.
+    pub(crate) fn present_frame(&self) -> bool {
+        let frame_duration = Duration::from_secs_f64(1.0 / self.framerate);
+        let mut stats = self.frame_stats.get();
+        let frame = self.frames.pop_due(
+            queue::running_time(&self.source),
+            frame_duration,
+            &mut stats,
+        );
+        self.frame_stats.set(stats);
+        let Some(frame) = frame else {
+            return false;
+        };
+        self.frame.store(Some(frame));
+        true
     }
 }
 
 /// A multimedia video loaded from a URI (e.g., a local file path or HTTP stream).
 #[derive(Debug)]
-pub struct Video(pub(crate) RwLock<Internal>);
+pub struct Video(pub(crate) Arc<RwLock<Internal>>);
 
 impl Drop for Video {
     fn drop(&mut self) {
-        let inner = self.0.get_mut().expect("failed to lock");
+        // the workers set dynamics whose callbacks may lock the video, so they are
+        // joined after the lock is released
+        let (source, monitor, worker, bus_worker) = {
+            let mut inner = self.0.write().expect("failed to lock");
+            inner.alive.store(false, Ordering::SeqCst);
+            (
+                inner.source.clone(),
+                inner.device_monitor.take(),
+                inner.worker.take(),
+                inner.bus_worker.take(),
+            )
+        };
 
-        inner
-            .source
+        source
             .set_state(gst::State::Null)
             .expect("failed to set state");
 
-        inner.alive.store(false, Ordering::SeqCst);
-        if let Some(worker) = inner.worker.take() {
+        if let Some(monitor) = monitor {
+            monitor.stop();
+        }
+
+        if let Some(worker) = worker {
             worker.join().expect("failed to stop video thread");
         }
+        if let Some(bus_worker) = bus_worker {
+            bus_worker.join().expect("failed to stop bus thread");
+        }
     }
 }
 
@@ -201,7 +1254,7 @@ impl Video {
     pub fn new(uri: &url::Url) -> Result<Self, Error> {
         gst::init()?;
 
-        let pipeline = format!("playbin uri=\"{}\" text-sink=\"appsink name=iced_text sync=true caps=text/x-raw\" video-sink=\"videoscale ! videoconvert ! appsink name=cushy_video drop=true caps=video/x-raw,format=NV12,pixel-aspect-ratio=1/1\"", uri.as_str());
+        let pipeline = format!("playbin uri=\"{}\" text-sink=\"appsink name=iced_text sync=true caps=text/x-raw\" video-sink=\"videoscale ! videoconvert ! appsink name=cushy_video max-buffers=1\"", uri.as_str());
         let pipeline = gst::parse::launch(pipeline.as_ref())?
             .downcast::<gst::Pipeline>()
             .unwrap();
@@ -217,6 +1270,12 @@ impl Video {
             .unwrap();
         let video_sink = bin.by_name("cushy_video").unwrap();
         let video_sink = video_sink.downcast::<gst_app::AppSink>().unwrap();
+        video_sink.set_caps(Some(
+            &gst_video::VideoCapsBuilder::new()
+                .format_list(FrameFormat::SUPPORTED)
+                .pixel_aspect_ratio(gst::Fraction::new(1, 1))
+                .build(),
+        ));
 
         let text_sink: gst::Element = pipeline.property("text-sink");
         let text_sink = text_sink.downcast::<gst_app::AppSink>().unwrap();
@@ -224,8 +1283,26 @@ impl Video {
         Self::from_gst_pipeline(pipeline, video_sink, Some(text_sink))
     }
 
+    /// Create a new video like [`Video::new`], without blocking the calling thread.
+    /// Resolves once the media capabilities are negotiated, which may take a while for network sources.
+    ///
+    /// Must be awaited within a tokio runtime, such as the one cushy runs with.
+    /// The future doesn't borrow `uri`, so it can be spawned or passed to [`VideoPlayer::loading`](crate::player::VideoPlayer::loading).
+    pub fn new_async(uri: &url::Url) -> impl Future<Output = Result<Self, Error>> + Send + 'static {
+        let uri = uri.clone();
+        async move {
+            tokio::task::spawn_blocking(move || Self::new(&uri))
+                .await
+                .map_err(|_| Error::Sync)?
+        }
+    }
+
     /// Creates a new video based on an existing GStreamer pipeline and appsink.
-    /// Expects an `appsink` plugin with `caps=video/x-raw,format=NV12`.
+    /// Expects an `appsink` plugin with `caps=video/x-raw` in one of the formats
+    /// `NV12`, `I420`, `P010_10LE`, `P016_LE`, `RGBA` or `BGRA`.
+    /// Any resolution works, padded row strides are respected.
+    /// The `appsink` should sync to the clock (the default) and keep its buffers (`drop=false`),
+    /// its `ts-offset` is changed so frames arrive ahead of the time they're shown.
     ///
     /// An optional `text_sink` can be provided, which enables subtitle messages
     /// to be emitted.
@@ -243,19 +1320,32 @@ impl Video {
 
         let pad = video_sink.pads().first().cloned().unwrap();
 
+        track::watch_tracks(&pipeline);
+        // keep any filter set up by the caller. `scaletempo` is installed before the first
+        // preroll, so the first speed change doesn't have to reload the audio path.
+        let audio_filter = AudioFilter {
+            preserve_pitch: true,
+            ..AudioFilter::default()
+        };
+        let preserve_pitch = pipeline.has_property("audio-filter", None)
+            && pipeline
+                .property::<Option<gst::Element>>("audio-filter")
+                .is_none()
+            && gst::ElementFactory::find("scaletempo").is_some()
+            && audio_filter.build().is_some_and(|filter| {
+                pipeline.set_property("audio-filter", filter);
+                true
+            });
         pipeline.set_state(gst::State::Playing)?;
 
         // wait for up to 5 seconds until the decoder gets the source capabilities
         pipeline.state(gst::ClockTime::from_seconds(5)).0?;
 
-        // extract resolution and framerate
-        // TODO(jazzfool): maybe we want to extract some other information too?
+        // extract resolution and framerate, other information is collected from tags by the bus worker
         let caps = pad.current_caps().ok_or(Error::Caps)?;
+        let info = caps_info(&caps)?;
+        let size = (info.width() as i32, info.height() as i32);
         let s = caps.structure(0).ok_or(Error::Caps)?;
-        let width = s.get::<i32>("width").map_err(|_| Error::Caps)?;
-        let height = s.get::<i32>("height").map_err(|_| Error::Caps)?;
-        // resolution should be mod4
-        let width = ((width + 4 - 1) / 4) * 4;
         let framerate = s
             .get::<gst::Fraction>("framerate")
             .map_err(|_| Error::Caps)?;
@@ -268,6 +1358,8 @@ impl Video {
         {
             return Err(Error::Framerate(framerate));
         }
+        // frames leave the sink ahead of time, the frame queue holds them until they're due
+        video_sink.set_ts_offset(-(queue::lead(framerate).nseconds() as i64));
 
         let duration = Duration::from_nanos(
             pipeline
@@ -276,58 +1368,151 @@ impl Video {
                 .unwrap_or(0),
         );
 
-        let sync_av = pipeline.has_property("av-offset", None);
+        // chapters are posted on the bus too, but only once they are found by the demuxer
+        let mut toc_query = gst::query::Toc::new();
+        let chapters = if pipeline.query(&mut toc_query) {
+            let (toc, _updated) = toc_query.result();
+            chapter::from_toc(toc)
+        } else {
+            Vec::new()
+        };
+
+        let pipeline_volume = if pipeline.has_property("volume", None) {
+            pipeline.property("volume")
+        } else {
+            1.0
+        };
+        let pipeline_muted = pipeline.has_property("mute", None) && pipeline.property("mute");
 
-        // NV12 = 12bpp
-        let frame = Arc::new(Mutex::new(vec![
-            0u8;
-            (width as usize * height as usize * 3)
-                .div_ceil(2)
-        ]));
+        let frame = Arc::new(FrameSlot::empty());
         let alive = Arc::new(AtomicBool::new(true));
-        let last_frame_time = Arc::new(Mutex::new(Instant::now()));
+        let frames = Arc::new(FrameQueue::default());
 
         let subtitles = Dynamic::new(None);
         let upload_frame = Dynamic::new(());
-
-        let frame_ref = Arc::clone(&frame);
+        let events = Dynamic::new(None);
+        let size = Dynamic::new(size);
+        let colorimetry = Dynamic::new(Colorimetry::from_info(&info));
+        let is_eos = Dynamic::new(false);
+        let looping = Arc::new(AtomicBool::new(false));
+        let segment_looping = Arc::new(AtomicBool::new(false));
+        let speed = Dynamic::new(1.0);
+        let trick_mode = Dynamic::new(TrickMode::default());
+        let state = Dynamic::new(PlayerState::default());
+        let position = Dynamic::new(Duration::ZERO);
+        let position_interval = Dynamic::new(Duration::from_millis(100));
+        let duration = Dynamic::new(duration);
+        let track_preferences = Dynamic::new(TrackPreferences::default());
+        let metadata = Dynamic::new(MediaMetadata::default());
+        let orientation = Dynamic::new(Orientation::default());
+        let chapters = Dynamic::new(chapters);
+        let current_chapter = Dynamic::new(None);
+        let audio_levels = Dynamic::new(AudioLevels::default());
+        let audio_spectrum = Dynamic::new(AudioSpectrum::default());
+        let audio_reload = Dynamic::new(None);
+        let pending_step = Dynamic::new(None);
+        let paused = Dynamic::new(false);
+
+        let frames_ref = Arc::clone(&frames);
         let alive_ref = Arc::clone(&alive);
-        let last_frame_time_ref = Arc::clone(&last_frame_time);
 
         let subtitles_ref = subtitles.clone();
         let upload_frame_ref = upload_frame.clone();
+        let events_ref = events.clone();
+        let size_ref = size.clone();
+        let colorimetry_ref = colorimetry.clone();
 
         let pipeline_ref = pipeline.clone();
 
         let worker = std::thread::spawn(move || {
             let mut clear_subtitles_at = None;
+            let mut peak_luminance = color::peak_luminance(&caps);
+            let mut last_caps = Some(caps);
+            let mut info = info;
+            // the preroll sample can be pulled repeatedly while paused
+            let mut last_preroll: Option<gst::Sample> = None;
+            // the appsink returns at once at the end of the stream or while flushing, wait anyway
+            let pull_sample = |timeout: gst::ClockTime| {
+                let start = Instant::now();
+                let sample = video_sink.try_pull_sample(timeout);
+                if sample.is_none() {
+                    std::thread::sleep(Duration::from(timeout).saturating_sub(start.elapsed()));
+                }
+                sample
+            };
 
             while alive_ref.load(Ordering::Acquire) {
-                if let Err(gst::FlowError::Error) = (|| -> Result<(), gst::FlowError> {
-                    let sample =
-                        if pipeline_ref.state(gst::ClockTime::ZERO).1 != gst::State::Playing {
-                            video_sink
-                                .try_pull_preroll(gst::ClockTime::from_mseconds(16))
-                                .ok_or(gst::FlowError::Eos)?
-                        } else {
-                            video_sink
-                                .try_pull_sample(gst::ClockTime::from_mseconds(16))
-                                .ok_or(gst::FlowError::Eos)?
-                        };
+                let now = queue::running_time(&pipeline_ref);
+                if frames_ref.announce_due(now) {
+                    upload_frame_ref.map_mut(|mut f| *f = ());
+                }
+                // wake up in time for the next queued frame
+                let timeout = frames_ref
+                    .next_due_in(now)
+                    .map_or(PULL_TIMEOUT, |due_in| due_in.min(PULL_TIMEOUT));
+                // the sink blocks the decoder until there's room in the queue again
+                if frames_ref.is_full() {
+                    frames_ref.drop_stale(now);
+                    if frames_ref.is_full() {
+                        frames_ref.wait_taken(timeout.into());
+                        continue;
+                    }
+                }
 
-                    *last_frame_time_ref
-                        .lock()
-                        .map_err(|_| gst::FlowError::Error)? = Instant::now();
+                if let Err(gst::FlowError::Error) = (|| -> Result<(), gst::FlowError> {
+                    let playing = pipeline_ref.state(gst::ClockTime::ZERO).1 == gst::State::Playing;
+                    let preroll =
+                        video_sink
+                            .try_pull_preroll(gst::ClockTime::ZERO)
+                            .filter(|sample| {
+                                !playing
+                                    && last_preroll
+                                        .as_ref()
+                                        .map_or(true, |last| last.as_ptr() != sample.as_ptr())
+                            });
+                    let sample = if let Some(sample) = preroll {
+                        last_preroll = Some(sample.clone());
+                        sample
+                    } else {
+                        // times out while paused, new preroll samples, e.g. after a step, are taken next time
+                        let sample = pull_sample(timeout).ok_or(gst::FlowError::Eos)?;
+                        // the preroll buffer is rendered again when playback resumes
+                        let prerolled = last_preroll.take().is_some_and(|last| {
+                            last.buffer().map(|b| b.as_ptr()) == sample.buffer().map(|b| b.as_ptr())
+                        });
+                        if prerolled {
+                            return Ok(());
+                        }
+                        sample
+                    };
 
-                    let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+                    let buffer = sample.buffer_owned().ok_or(gst::FlowError::Error)?;
                     let pts = buffer.pts().unwrap_or_default();
-                    let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
-
-                    let mut frame = frame_ref.lock().map_err(|_| gst::FlowError::Error)?;
-                    let frame_len = frame.len();
-                    frame.copy_from_slice(&map.as_slice()[..frame_len]);
+                    let segment = sample
+                        .segment()
+                        .and_then(|segment| segment.downcast_ref::<gst::ClockTime>());
+                    let running_time = segment.and_then(|segment| segment.to_running_time(pts));
+                    let position = segment.and_then(|segment| segment.to_stream_time(pts));
+
+                    // adaptive streams and some cameras change resolution mid-stream
+                    let caps = sample.caps().ok_or(gst::FlowError::NotNegotiated)?;
+                    if last_caps.as_deref() != Some(caps) {
+                        info = caps_info(caps).map_err(|_| gst::FlowError::NotNegotiated)?;
+                        peak_luminance = color::peak_luminance(caps);
+                        last_caps = Some(caps.to_owned());
+                        colorimetry_ref.set(Colorimetry::from_info(&info));
+                        let size = (info.width() as i32, info.height() as i32);
+                        if size_ref.replace(size).is_some() {
+                            let (width, height) = size;
+                            events_ref.map_mut(|mut e| {
+                                *e = Some(VideoEvent::SizeChanged { width, height })
+                            });
+                        }
+                    }
 
-                    upload_frame_ref.map_mut(|mut f| *f = ());
+                    let frame = Frame::new(buffer, &info, peak_luminance, running_time, position)
+                        .map_err(|_| gst::FlowError::Error)?;
+                    frames_ref.push(frame);
 
                     if let Some(at) = clear_subtitles_at {
                         if pts >= at {
@@ -340,17 +1525,25 @@ impl Video {
                         .as_ref()
                         .and_then(|sink| sink.try_pull_sample(gst::ClockTime::from_seconds(0)));
                     if let Some(text) = text {
+                        // plain `utf8` text may contain `<` without being markup
+                        let markup = text
+                            .caps()
+                            .and_then(|caps| caps.structure(0))
+                            .and_then(|structure| structure.get::<&str>("format").ok())
+                            .is_some_and(|format| format == "pango-markup");
                         let text = text.buffer().ok_or(gst::FlowError::Error)?;
                         let pts = text.pts().unwrap_or_default();
                         let duration = text.duration().unwrap_or(gst::ClockTime::ZERO);
                         let map = text.map_readable().map_err(|_| gst::FlowError::Error)?;
 
-                        let text = html_escape::decode_html_entities(
-                            std::str::from_utf8(map.as_slice())
-                                .map_err(|_| gst::FlowError::Error)?,
-                        )
-                        .to_string();
-                        subtitles_ref.set(Some(text));
+                        let text = std::str::from_utf8(map.as_slice())
+                            .map_err(|_| gst::FlowError::Error)?;
+                        let cue = if markup {
+                            SubtitleCue::from_markup(text)
+                        } else {
+                            SubtitleCue::plain(text)
+                        };
+                        subtitles_ref.set(Some(cue));
 
                         clear_subtitles_at = Some(pts + duration);
                     }
@@ -362,32 +1555,93 @@ impl Video {
             }
         });
 
-        Ok(Video(RwLock::new(Internal {
+        let bus = pipeline.bus().ok_or(Error::Bus)?;
+        let bus_worker = BusWorker {
+            source: pipeline.clone(),
+            events: events.clone(),
+            is_eos: is_eos.clone(),
+            looping: Arc::clone(&looping),
+            segment_looping: Arc::clone(&segment_looping),
+            speed: speed.clone(),
+            trick_mode: trick_mode.clone(),
+            state: state.clone(),
+            position: position.clone(),
+            position_interval: position_interval.clone(),
+            duration: duration.clone(),
+            track_preferences: track_preferences.clone(),
+            metadata: metadata.clone(),
+            orientation: orientation.clone(),
+            chapters: chapters.clone(),
+            current_chapter: current_chapter.clone(),
+            audio_levels: audio_levels.clone(),
+            audio_spectrum: audio_spectrum.clone(),
+            analysis: VecDeque::new(),
+            audio_reload: audio_reload.clone(),
+            pending_step: pending_step.clone(),
+            paused: paused.clone(),
+            buffering: false,
+        }
+        .spawn(bus.clone(), Arc::clone(&alive));
+
+        let internal = Internal {
             id,
 
-            bus: pipeline.bus().unwrap(),
+            bus,
             source: pipeline,
             alive,
             worker: Some(worker),
+            bus_worker: Some(bus_worker),
 
-            width,
-            height,
+            size,
+            colorimetry,
+            colorimetry_override: Dynamic::new(None),
             framerate,
             duration,
-            speed: 1.0,
-            sync_av,
+            speed,
+            trick_mode,
+            preserve_pitch: Dynamic::new(preserve_pitch),
+            audio_analysis: Dynamic::new(false),
+            equalizer: Dynamic::new(Equalizer::FLAT),
+            balance: Dynamic::new(0.0),
+            mono: Dynamic::new(false),
+            audio_filter: AudioFilter {
+                preserve_pitch,
+                ..AudioFilter::default()
+            },
+            audio_reload,
+            pending_step,
+            audio_levels,
+            audio_spectrum,
+            audio_device: Dynamic::new(None),
+            audio_devices: Dynamic::new(Vec::new()),
+            device_monitor: None,
 
             frame,
-            last_frame_time,
-            looping: false,
-            is_eos: false,
-            restart_stream: false,
-            sync_av_avg: 0,
-            sync_av_counter: 0,
+            frames,
+            frame_stats: Dynamic::new(FrameStats::default()),
+            looping,
+            segment_looping,
+            is_eos,
 
             subtitles,
             upload_frame,
-        })))
+            events,
+            state,
+            position,
+            position_interval,
+            paused,
+            muted: Dynamic::new(pipeline_muted),
+            volume: Dynamic::new(pipeline_volume),
+            track_preferences,
+            metadata,
+            orientation,
+            orientation_override: Dynamic::new(None),
+            chapters,
+            current_chapter,
+        };
+        internal.bind_controls();
+
+        Ok(Video(Arc::new(RwLock::new(internal))))
     }
 
     pub(crate) fn read(&self) -> impl Deref<Target = Internal> + '_ {
@@ -399,12 +1653,25 @@ impl Video {
     }
 
     pub(crate) fn get_mut(&mut self) -> impl DerefMut<Target = Internal> + '_ {
-        self.0.get_mut().expect("lock")
+        // handles share the lock, so exclusive access to `self` doesn't imply exclusive access
+        self.write()
+    }
+
+    /// Creates a cloneable handle to control this video from anywhere, e.g. from buttons or
+    /// background tasks, even after the video was moved into a [`VideoPlayer`](crate::player::VideoPlayer).
+    pub fn handle(&self) -> VideoHandle {
+        VideoHandle(Arc::downgrade(&self.0))
     }
 
     /// Get the size/resolution of the video as `(width, height)`.
     pub fn size(&self) -> (i32, i32) {
-        (self.read().width, self.read().height)
+        self.read().size.get()
+    }
+
+    /// Returns a dynamic source of the size/resolution, which can change mid-stream.
+    #[must_use]
+    pub fn size_reader(&self) -> DynamicReader<(i32, i32)> {
+        self.read().size.clone().into_reader()
     }
 
     /// Get the framerate of the video as frames per second.
@@ -417,8 +1684,9 @@ impl Video {
     ///
     /// This uses a linear scale, for example `0.5` is perceived as half as loud.
     pub fn set_volume(&mut self, volume: f64) {
-        self.get_mut().source.set_property("volume", volume);
-        self.set_muted(self.muted()); // for some reason gstreamer unmutes when changing volume?
+        // set without the lock, the dynamic notifies its observers right away
+        let dynamic = self.read().volume.clone();
+        dynamic.set(volume);
     }
 
     /// Get the volume multiplier of the audio.
@@ -428,7 +1696,8 @@ impl Video {
 
     /// Set if the audio is muted or not, without changing the volume.
     pub fn set_muted(&mut self, muted: bool) {
-        self.get_mut().source.set_property("mute", muted);
+        let dynamic = self.read().muted.clone();
+        dynamic.set(muted);
     }
 
     /// Get if the audio is muted or not.
@@ -438,22 +1707,31 @@ impl Video {
 
     /// Get if the stream ended or not.
     pub fn eos(&self) -> bool {
-        self.read().is_eos
+        self.read().is_eos.get()
     }
 
     /// Get if the media will loop or not.
     pub fn looping(&self) -> bool {
-        self.read().looping
+        self.read().looping.load(Ordering::SeqCst)
     }
 
     /// Set if the media will loop or not.
+    ///
+    /// Where the source supports segment seeks, the loop is seamless. Other sources are
+    /// restarted when they reach the end, which may show a short gap.
     pub fn set_looping(&mut self, looping: bool) {
-        self.get_mut().looping = looping;
+        let ended = self.get_mut().set_looping(looping);
+        if ended {
+            let is_eos = self.read().is_eos.clone();
+            is_eos.set(false);
+        }
     }
 
     /// Set if the media is paused or not.
     pub fn set_paused(&mut self, paused: bool) {
-        self.get_mut().set_paused(paused)
+        let dynamic = self.read().paused.clone();
+        // always notify, so the pipeline state is applied again even if the value didn't change
+        dynamic.map_mut(|mut p| *p = paused);
     }
 
     /// Get if the media is paused or not.
@@ -468,44 +1746,160 @@ impl Video {
         self.get_mut().seek(position, accurate)
     }
 
-    /// Set the playback speed of the media.
-    /// The default speed is `1.0`.
+    /// Set the playback speed of the media, decoding every frame.
+    /// The default speed is `1.0`, negative speeds play in reverse.
     pub fn set_speed(&mut self, speed: f64) -> Result<(), Error> {
         self.get_mut().set_speed(speed)
     }
 
     /// Get the current playback speed.
     pub fn speed(&self) -> f64 {
-        self.read().speed
+        self.read().speed.get()
+    }
+
+    /// Plays at `rate` with the given trick mode, e.g. `8.0` with [`TrickMode::KeyUnits`]
+    /// to fast-forward or `-4.0` to rewind. Falls back to a simpler mode if the demuxer
+    /// doesn't support the requested one, and returns the mode that is used.
+    ///
+    /// [`Video::set_speed`] goes back to decoding every frame.
+    pub fn set_trick_mode(&mut self, rate: f64, mode: TrickMode) -> Result<TrickMode, Error> {
+        self.get_mut().set_trick_mode(rate, mode)
+    }
+
+    /// Set if the pitch of the audio is kept when playing faster or slower, on by default.
+    /// Uses the `scaletempo` element, so speech stays understandable at e.g. `1.5` or `2.0`.
+    ///
+    /// Changing this reloads the audio path to add or remove `scaletempo`,
+    /// which briefly interrupts playback.
+    pub fn set_preserve_pitch(&mut self, preserve_pitch: bool) -> Result<(), Error> {
+        self.get_mut().set_preserve_pitch(preserve_pitch)
+    }
+
+    /// Get if the pitch of the audio is kept when changing the speed, see [`Video::set_preserve_pitch`].
+    pub fn preserve_pitch(&self) -> bool {
+        self.read().preserve_pitch.get()
+    }
+
+    /// Turns the analysis of the audio on or off, off by default.
+    /// While on, [`Video::audio_levels`] and [`Video::audio_spectrum`] are updated as the audio plays,
+    /// e.g. for VU meters or a spectrum visualizer.
+    ///
+    /// Changing this reloads the audio path, which briefly interrupts playback.
+    pub fn set_audio_analysis(&mut self, analysis: bool) -> Result<(), Error> {
+        self.get_mut().set_audio_analysis(analysis)
+    }
+
+    /// Get if the audio is analyzed, see [`Video::set_audio_analysis`].
+    pub fn audio_analysis(&self) -> bool {
+        self.read().audio_analysis.get()
+    }
+
+    /// Set the gains of the graphic equalizer, e.g. from an [`EqualizerPreset`](crate::audio::EqualizerPreset).
+    /// Flat by default.
+    ///
+    /// The first time the equalizer is used, the audio path is reloaded, which briefly
+    /// interrupts playback. Later changes apply immediately.
+    pub fn set_equalizer(&mut self, equalizer: impl Into<Equalizer>) -> Result<(), Error> {
+        self.get_mut().set_equalizer(equalizer.into())
+    }
+
+    /// Get the gains of the graphic equalizer, see [`Video::set_equalizer`].
+    pub fn equalizer(&self) -> Equalizer {
+        self.read().equalizer.get()
+    }
+
+    /// Set the stereo balance, from `-1.0` (left only) over `0.0` (centered, the default)
+    /// to `1.0` (right only). Mono audio is panned instead.
+    ///
+    /// The first time the balance is moved, the audio path is reloaded, which briefly
+    /// interrupts playback. Later changes apply immediately.
+    pub fn set_balance(&mut self, balance: f32) -> Result<(), Error> {
+        self.get_mut().set_balance(balance)
+    }
+
+    /// Get the stereo balance, see [`Video::set_balance`].
+    pub fn balance(&self) -> f32 {
+        self.read().balance.get()
+    }
+
+    /// Set if all audio channels are mixed down to mono, off by default.
+    ///
+    /// The first time this is turned on, the audio path is reloaded, which briefly
+    /// interrupts playback. Later changes apply immediately.
+    pub fn set_mono(&mut self, mono: bool) -> Result<(), Error> {
+        self.get_mut().set_mono(mono)
+    }
+
+    /// Get if the audio is mixed down to mono, see [`Video::set_mono`].
+    pub fn mono(&self) -> bool {
+        self.read().mono.get()
+    }
+
+    /// Returns a dynamic source of the loudness per audio channel, see [`Video::set_audio_analysis`].
+    #[must_use]
+    pub fn audio_levels(&self) -> DynamicReader<AudioLevels> {
+        self.read().audio_levels.clone().into_reader()
+    }
+
+    /// Returns a dynamic source of the audio frequency spectrum, see [`Video::set_audio_analysis`].
+    #[must_use]
+    pub fn audio_spectrum(&self) -> DynamicReader<AudioSpectrum> {
+        self.read().audio_spectrum.clone().into_reader()
+    }
+
+    /// Returns a dynamic list of the audio output devices, updated as devices are plugged in
+    /// or removed. The changes are also published as [`VideoEvent::AudioDeviceAdded`] and
+    /// [`VideoEvent::AudioDeviceRemoved`].
+    ///
+    /// Devices are only watched once this was called.
+    pub fn audio_devices(&self) -> Result<DynamicReader<Vec<AudioDevice>>, Error> {
+        self.write().audio_devices()
+    }
+
+    /// Plays the audio on `device`, or on the default output if `None`.
+    ///
+    /// Changing this reloads the audio path, which briefly interrupts playback.
+    pub fn set_audio_device(&mut self, device: Option<AudioDevice>) -> Result<(), Error> {
+        self.get_mut().set_audio_device(device)
+    }
+
+    /// Get the audio output device, `None` for the default output.
+    pub fn audio_device(&self) -> Option<AudioDevice> {
+        self.read().audio_device.get()
+    }
+
+    /// Get the current trick mode, see [`Video::set_trick_mode`].
+    pub fn trick_mode(&self) -> TrickMode {
+        self.read().trick_mode.get()
     }
 
     /// Get the current playback position in time.
     pub fn position(&self) -> Duration {
-        Duration::from_nanos(
-            self.read()
-                .source
-                .query_position::<gst::ClockTime>()
-                .map_or(0, |pos| pos.nseconds()),
-        )
+        self.read().position()
     }
 
     /// Get the media duration.
     pub fn duration(&self) -> Duration {
-        self.read().duration
+        self.read().duration.get()
     }
 
     /// Restarts a stream; seeks to the first frame and unpauses, sets the `eos` flag to false.
     pub fn restart_stream(&mut self) -> Result<(), Error> {
-        self.get_mut().restart_stream()
+        let is_eos = self.read().is_eos.clone();
+        is_eos.set(false);
+        self.set_paused(false);
+        self.read().seek(0, false)
     }
 
     /// Set the subtitle URL to display.
     pub fn set_subtitle_url(&mut self, url: &url::Url) -> Result<(), Error> {
         let paused = self.paused();
-        let mut inner = self.get_mut();
-        inner.source.set_state(gst::State::Ready)?;
-        inner.source.set_property("suburi", url.as_str());
-        inner.set_paused(paused);
+        {
+            let inner = self.get_mut();
+            inner.source.set_state(gst::State::Ready)?;
+            inner.source.set_property("suburi", url.as_str());
+        }
+        self.set_paused(paused);
         Ok(())
     }
 
@@ -514,91 +1908,351 @@ impl Video {
         url::Url::parse(&self.read().source.property::<String>("suburi")).ok()
     }
 
+    /// Returns a dynamic source of the overall playback state.
+    #[must_use]
+    pub fn state(&self) -> DynamicReader<PlayerState> {
+        self.read().state.clone().into_reader()
+    }
+
+    /// Returns a dynamic source of the playback position.
+    /// It is updated at the rate set by [`Video::set_position_update_interval`].
+    #[must_use]
+    pub fn position_reader(&self) -> DynamicReader<Duration> {
+        self.read().position.clone().into_reader()
+    }
+
+    /// Set how often the dynamic position is updated. The default is every 100ms,
+    /// intervals below 10ms are raised to 10ms.
+    pub fn set_position_update_interval(&self, interval: Duration) {
+        let dynamic = self.read().position_interval.clone();
+        // the bus worker would spin polling the bus without a timeout
+        dynamic.set(interval.max(MIN_POSITION_INTERVAL));
+    }
+
+    /// Returns a dynamic source of the media duration, updated when it becomes known.
+    #[must_use]
+    pub fn duration_reader(&self) -> DynamicReader<Duration> {
+        self.read().duration.clone().into_reader()
+    }
+
+    /// Returns a dynamic source of the playback speed.
+    #[must_use]
+    pub fn speed_reader(&self) -> DynamicReader<f64> {
+        self.read().speed.clone().into_reader()
+    }
+
+    /// Returns a dynamic source of the end of stream flag.
+    #[must_use]
+    pub fn eos_reader(&self) -> DynamicReader<bool> {
+        self.read().is_eos.clone().into_reader()
+    }
+
+    /// Returns a dynamic that pauses or resumes playback when set, e.g. from a checkbox.
+    #[must_use]
+    pub fn paused_dynamic(&self) -> Dynamic<bool> {
+        self.read().paused.clone()
+    }
+
+    /// Returns a dynamic that mutes or unmutes the audio when set.
+    #[must_use]
+    pub fn muted_dynamic(&self) -> Dynamic<bool> {
+        self.read().muted.clone()
+    }
+
+    /// Returns a dynamic that changes the volume multiplier when set, e.g. from a slider.
+    #[must_use]
+    pub fn volume_dynamic(&self) -> Dynamic<f64> {
+        self.read().volume.clone()
+    }
+
+    /// Returns a dynamic source of the events posted on the pipeline bus.
+    /// Holds the most recent event, or `None` if nothing was posted yet.
+    #[must_use]
+    pub fn events(&self) -> DynamicReader<Option<VideoEvent>> {
+        self.read().events.clone().into_reader()
+    }
+
+    /// Lists the tracks of the given kind, with their language, codec and title if known.
+    pub fn tracks(&self, kind: TrackKind) -> Vec<Track> {
+        track::tracks(&self.read().source, kind)
+    }
+
+    /// Get the index of the active track of the given kind, or `None` if the kind is disabled.
+    pub fn current_track(&self, kind: TrackKind) -> Option<usize> {
+        track::current_track(&self.read().source, kind)
+    }
+
+    /// Switches the active track of the given kind, e.g. to change the audio language.
+    /// Passing `None` disables the kind, e.g. to hide subtitles.
+    pub fn select_track(&mut self, kind: TrackKind, index: Option<usize>) -> Result<(), Error> {
+        track::select_track(&self.get_mut().source, kind, index)
+    }
+
+    /// Set the preferred audio and subtitle languages as ISO 639 codes.
+    ///
+    /// Matching tracks are selected right away, and again whenever the tracks of the media change.
+    pub fn set_track_preferences(&mut self, preferences: TrackPreferences) {
+        let inner = self.get_mut();
+        apply_track_preferences(&inner.source, &preferences);
+        inner.track_preferences.set(preferences);
+    }
+
+    /// Get the preferred audio and subtitle languages.
+    pub fn track_preferences(&self) -> TrackPreferences {
+        self.read().track_preferences.get()
+    }
+
+    /// Get the metadata (title, codecs, cover art, ...) found in the media so far.
+    pub fn metadata(&self) -> MediaMetadata {
+        self.read().metadata.get()
+    }
+
+    /// Returns a dynamic source of the media metadata, updated as new tags arrive.
+    #[must_use]
+    pub fn metadata_reader(&self) -> DynamicReader<MediaMetadata> {
+        self.read().metadata.clone().into_reader()
+    }
+
+    /// Get the orientation the video should be shown in, read from its `image-orientation` tag.
+    /// Applied automatically by [`VideoPlayer`](crate::player::VideoPlayer), unless overridden.
+    pub fn orientation(&self) -> Orientation {
+        self.read().orientation.get()
+    }
+
+    /// Returns a dynamic source of the orientation from the tags, see [`Video::orientation`].
+    #[must_use]
+    pub fn orientation_reader(&self) -> DynamicReader<Orientation> {
+        self.read().orientation.clone().into_reader()
+    }
+
+    /// Overrides the orientation read from the tags, to rotate or flip the video by hand,
+    /// e.g. `Some(video.orientation().rotated_clockwise())`. `None` goes back to the tags.
+    pub fn set_orientation_override(&self, orientation: Option<Orientation>) {
+        self.read().orientation_override.set(orientation);
+    }
+
+    /// Get the orientation override, see [`Video::set_orientation_override`].
+    pub fn orientation_override(&self) -> Option<Orientation> {
+        self.read().orientation_override.get()
+    }
+
+    /// Get the presentation statistics, see [`Video::frame_stats_reader`].
+    pub fn frame_stats(&self) -> FrameStats {
+        self.read().frame_stats.get()
+    }
+
+    /// Returns a dynamic source of the presentation statistics, updated as frames are shown
+    /// by a [`VideoPlayer`](crate::player::VideoPlayer).
+    #[must_use]
+    pub fn frame_stats_reader(&self) -> DynamicReader<FrameStats> {
+        self.read().frame_stats.clone().into_reader()
+    }
+
+    /// Get the colorimetry of the video stream, used to convert its colors for display.
+    pub fn colorimetry(&self) -> Colorimetry {
+        self.read().colorimetry.get()
+    }
+
+    /// Returns a dynamic source of the stream colorimetry, which may change mid-stream.
+    #[must_use]
+    pub fn colorimetry_reader(&self) -> DynamicReader<Colorimetry> {
+        self.read().colorimetry.clone().into_reader()
+    }
+
+    /// Overrides the colorimetry read from the stream, e.g. for files with wrong or missing
+    /// color information. `None` goes back to the stream colorimetry.
+    pub fn set_colorimetry_override(&self, colorimetry: Option<Colorimetry>) {
+        self.read().colorimetry_override.set(colorimetry);
+    }
+
+    /// Get the colorimetry override, see [`Video::set_colorimetry_override`].
+    pub fn colorimetry_override(&self) -> Option<Colorimetry> {
+        self.read().colorimetry_override.get()
+    }
+
+    /// Get the chapters of the media, ordered by their start.
+    pub fn chapters(&self) -> Vec<Chapter> {
+        self.read().chapters.get()
+    }
+
+    /// Returns a dynamic source of the chapters, updated when a table of contents is found.
+    #[must_use]
+    pub fn chapters_reader(&self) -> DynamicReader<Vec<Chapter>> {
+        self.read().chapters.clone().into_reader()
+    }
+
+    /// Returns a dynamic source of the index of the chapter currently playing.
+    /// It is updated together with [`Video::position_reader`].
+    #[must_use]
+    pub fn current_chapter(&self) -> DynamicReader<Option<usize>> {
+        self.read().current_chapter.clone().into_reader()
+    }
+
+    /// Steps `frames` frames forward, or backward if negative, pausing playback first.
+    /// Forward steps use step events, sent once pausing has finished,
+    /// backward steps seek to the frame based on the framerate.
+    pub fn step_frames(&mut self, frames: i64) -> Result<(), Error> {
+        if frames != 0 && !self.paused() {
+            self.set_paused(true);
+        }
+        self.get_mut().step_frames(frames)
+    }
+
+    /// Jumps to the start of the chapter with the given index.
+    pub fn seek_to_chapter(&mut self, index: usize) -> Result<(), Error> {
+        self.get_mut().seek_to_chapter(index)
+    }
+
+    /// Jumps to the start of the next chapter.
+    pub fn next_chapter(&mut self) -> Result<(), Error> {
+        self.get_mut().next_chapter()
+    }
+
+    /// Jumps to the start of the current chapter, or to the previous chapter
+    /// if the current one only just started. Between chapters, the one before counts as current.
+    pub fn previous_chapter(&mut self) -> Result<(), Error> {
+        self.get_mut().previous_chapter()
+    }
+
     /// Get the underlying GStreamer pipeline.
     pub fn pipeline(&self) -> gst::Pipeline {
         self.read().source.clone()
     }
 
-    // /// Generates a list of thumbnails based on a set of positions in the media, downscaled by a given factor.
-    // ///
-    // /// Slow; only needs to be called once for each instance.
-    // /// It's best to call this at the very start of playback, otherwise the position may shift.
-    // pub fn thumbnails<I>(
-    //     &mut self,
-    //     positions: I,
-    //     downscale: NonZeroU8,
-    // ) -> Result<Vec<img::Handle>, Error>
-    // where
-    //     I: IntoIterator<Item = Position>,
-    // {
-    //     let downscale = u8::from(downscale) as u32;
-
-    //     let paused = self.paused();
-    //     let muted = self.muted();
-    //     let pos = self.position();
-
-    //     self.set_paused(false);
-    //     self.set_muted(true);
-
-    //     let out = {
-    //         let inner = self.read();
-    //         let width = inner.width;
-    //         let height = inner.height;
-    //         positions
-    //             .into_iter()
-    //             .map(|pos| {
-    //                 inner.seek(pos, true)?;
-    //                 inner.upload_frame.store(false, Ordering::SeqCst);
-    //                 while !inner.upload_frame.load(Ordering::SeqCst) {
-    //                     std::hint::spin_loop();
-    //                 }
-    //                 Ok(img::Handle::from_rgba(
-    //                     inner.width as u32 / downscale,
-    //                     inner.height as u32 / downscale,
-    //                     yuv_to_rgba(
-    //                         &inner.frame.lock().map_err(|_| Error::Lock)?,
-    //                         width as _,
-    //                         height as _,
-    //                         downscale,
-    //                     ),
-    //                 ))
-    //             })
-    //             .collect()
-    //     };
-
-    //     self.set_paused(paused);
-    //     self.set_muted(muted);
-    //     self.seek(pos, true)?;
-
-    //     out
-    // }
-}
-
-// fn yuv_to_rgba(yuv: &[u8], width: u32, height: u32, downscale: u32) -> Vec<u8> {
-//     let uv_start = width * height;
-//     let mut rgba = vec![];
-
-//     for y in 0..height / downscale {
-//         for x in 0..width / downscale {
-//             let x_src = x * downscale;
-//             let y_src = y * downscale;
+    /// Generates a list of thumbnails based on a set of positions in the media, downscaled by a given factor.
+    ///
+    /// The thumbnails are decoded by a separate pipeline, so playback is not disturbed.
+    /// Slow; only needs to be called once for each instance.
+    /// Only works for [`Video`]s created from a URI, see [`Video::new`].
+    pub fn thumbnails<I>(
+        &self,
+        positions: I,
+        downscale: NonZeroU8,
+    ) -> Result<Vec<LazyTexture>, Error>
+    where
+        I: IntoIterator<Item = Position>,
+    {
+        let (uri, width, height) = {
+            let inner = self.read();
+            if !inner.source.has_property("uri", None) {
+                return Err(Error::Uri);
+            }
+            let uri = inner
+                .source
+                .property::<Option<String>>("uri")
+                .ok_or(Error::Uri)?;
+            let (width, height) = inner.size.get();
+            (uri, width, height)
+        };
 
-//             let uv_i = uv_start + width * (y_src / 2) + x_src / 2 * 2;
+        let downscale = i32::from(u8::from(downscale));
+        let width = (width / downscale).max(1);
+        let height = (height / downscale).max(1);
 
-//             let y = yuv[(y_src * width + x_src) as usize] as f32;
-//             let u = yuv[uv_i as usize] as f32;
-//             let v = yuv[(uv_i + 1) as usize] as f32;
+        let pipeline = format!("uridecodebin uri=\"{uri}\" ! videoconvert ! videoscale ! appsink name=cushy_thumbnail sync=false caps=video/x-raw,format=RGBA,pixel-aspect-ratio=1/1,width={width},height={height}");
+        let pipeline = gst::parse::launch(pipeline.as_ref())?
+            .downcast::<gst::Pipeline>()
+            .map_err(|_| Error::Cast)?;
+        let sink = pipeline
+            .by_name("cushy_thumbnail")
+            .ok_or_else(|| Error::AppSink("cushy_thumbnail".into()))?
+            .downcast::<gst_app::AppSink>()
+            .map_err(|_| Error::Cast)?;
+
+        let out = (|| -> Result<Vec<LazyTexture>, Error> {
+            pipeline.set_state(gst::State::Paused)?;
+            pipeline.state(gst::ClockTime::from_seconds(5)).0?;
+
+            positions
+                .into_iter()
+                .map(|pos| -> Result<LazyTexture, Error> {
+                    pipeline.seek_simple(
+                        gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE,
+                        gst::GenericFormattedValue::from(pos),
+                    )?;
+                    // the flushing seek makes the pipeline preroll again, at the new position
+                    pipeline.state(gst::ClockTime::from_seconds(5)).0?;
+                    let sample = sink
+                        .try_pull_preroll(gst::ClockTime::from_seconds(5))
+                        .ok_or(Error::FrameTimeout)?;
+                    let buffer = sample.buffer().ok_or(Error::Caps)?;
+                    let map = buffer.map_readable().map_err(|_| Error::Caps)?;
+
+                    let len = width as usize * height as usize * 4;
+                    let rgba = map.as_slice().get(..len).ok_or(Error::Caps)?.to_vec();
+                    let image = image::RgbaImage::from_raw(width as u32, height as u32, rgba)
+                        .ok_or(Error::Caps)?;
+                    Ok(LazyTexture::from_image(
+                        image::DynamicImage::ImageRgba8(image),
+                        wgpu::FilterMode::Linear,
+                    ))
+                })
+                .collect()
+        })();
+
+        pipeline.set_state(gst::State::Null)?;
+
+        out
+    }
+}
 
-//             let r = 1.164 * (y - 16.0) + 1.596 * (v - 128.0);
-//             let g = 1.164 * (y - 16.0) - 0.813 * (v - 128.0) - 0.391 * (u - 128.0);
-//             let b = 1.164 * (y - 16.0) + 2.018 * (u - 128.0);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trick_mode_flags() {
+        use gst::SeekFlags as F;
+        // mode, seek flags, accuracy flag
+        let cases = [
+            (TrickMode::Off, F::empty(), F::ACCURATE),
+            (
+                TrickMode::Skip,
+                F::TRICKMODE | F::TRICKMODE_NO_AUDIO,
+                F::ACCURATE,
+            ),
+            (
+                TrickMode::KeyUnits,
+                F::TRICKMODE | F::TRICKMODE_KEY_UNITS | F::TRICKMODE_NO_AUDIO,
+                F::KEY_UNIT,
+            ),
+        ];
+        for (mode, seek_flags, accuracy_flag) in cases {
+            assert_eq!(mode.seek_flags(), seek_flags, "{mode:?}");
+            assert_eq!(mode.accuracy_flag(), accuracy_flag, "{mode:?}");
+        }
+    }
 
-//             rgba.push(r as u8);
-//             rgba.push(g as u8);
-//             rgba.push(b as u8);
-//             rgba.push(0xFF);
-//         }
-//     }
+    #[test]
+    fn trick_mode_fallback() {
+        let chain = |mut mode: TrickMode| {
+            let mut chain = vec![mode];
+            while let Some(next) = mode.fallback() {
+                chain.push(next);
+                mode = next;
+            }
+            chain
+        };
+        assert_eq!(
+            chain(TrickMode::KeyUnits),
+            [TrickMode::KeyUnits, TrickMode::Skip, TrickMode::Off]
+        );
+        assert_eq!(chain(TrickMode::Skip), [TrickMode::Skip, TrickMode::Off]);
+        assert_eq!(chain(TrickMode::Off), [TrickMode::Off]);
+    }
 
-//     rgba
-// }
+    #[test]
+    fn seek_range_follows_the_direction() {
+        // rate, start and stop of a seek to 5 in media running from 0 to 10
+        let cases = [
+            (1.0, (5, 10)),
+            (8.0, (5, 10)),
+            (0.5, (5, 10)),
+            (-1.0, (0, 5)),
+            (-4.0, (0, 5)),
+        ];
+        for (rate, expected) in cases {
+            assert_eq!(seek_range(rate, 5, 0, 10), expected, "rate {rate}");
+        }
+    }
+}